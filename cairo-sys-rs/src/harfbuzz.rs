@@ -0,0 +1,116 @@
+// Copyright 2020, The Gtk-rs Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <https://opensource.org/licenses/MIT>
+
+use libc::{c_char, c_int, c_uint, c_void};
+
+use FT_Face;
+
+pub type hb_bool_t = c_int;
+pub type hb_codepoint_t = u32;
+pub type hb_position_t = i32;
+pub type hb_mask_t = u32;
+pub type hb_tag_t = u32;
+pub type hb_script_t = hb_tag_t;
+pub type hb_direction_t = c_int;
+pub type hb_language_t = *const c_void;
+
+#[repr(C)]
+pub struct hb_face_t(c_void);
+
+#[repr(C)]
+pub struct hb_font_t(c_void);
+
+#[repr(C)]
+pub struct hb_buffer_t(c_void);
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct hb_glyph_info_t {
+    pub codepoint: hb_codepoint_t,
+    pub mask: hb_mask_t,
+    pub cluster: u32,
+    pub var1: u32,
+    pub var2: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct hb_glyph_position_t {
+    pub x_advance: hb_position_t,
+    pub y_advance: hb_position_t,
+    pub x_offset: hb_position_t,
+    pub y_offset: hb_position_t,
+    pub var: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct hb_feature_t {
+    pub tag: hb_tag_t,
+    pub value: u32,
+    pub start: c_uint,
+    pub end: c_uint,
+}
+
+pub const HB_DIRECTION_INVALID: hb_direction_t = 0;
+pub const HB_DIRECTION_LTR: hb_direction_t = 4;
+pub const HB_DIRECTION_RTL: hb_direction_t = 5;
+pub const HB_DIRECTION_TTB: hb_direction_t = 6;
+pub const HB_DIRECTION_BTT: hb_direction_t = 7;
+pub const HB_SCRIPT_INVALID: hb_script_t = 0;
+pub const HB_SCRIPT_UNKNOWN: hb_script_t = 0x5a7a_7a7a; // 'Zzzz'
+
+extern "C" {
+    pub fn hb_ft_face_create_referenced(ft_face: FT_Face) -> *mut hb_face_t;
+    pub fn hb_face_destroy(face: *mut hb_face_t);
+    pub fn hb_face_get_upem(face: *mut hb_face_t) -> c_uint;
+
+    pub fn hb_font_create(face: *mut hb_face_t) -> *mut hb_font_t;
+    pub fn hb_font_destroy(font: *mut hb_font_t);
+    pub fn hb_font_set_scale(font: *mut hb_font_t, x_scale: c_int, y_scale: c_int);
+    pub fn hb_font_set_ppem(font: *mut hb_font_t, x_ppem: c_uint, y_ppem: c_uint);
+    pub fn hb_font_set_var_coords_normalized(
+        font: *mut hb_font_t,
+        coords: *const c_int,
+        coords_length: c_uint,
+    );
+
+    pub fn hb_buffer_create() -> *mut hb_buffer_t;
+    pub fn hb_buffer_destroy(buffer: *mut hb_buffer_t);
+    pub fn hb_buffer_allocation_successful(buffer: *mut hb_buffer_t) -> hb_bool_t;
+    pub fn hb_buffer_add_utf8(
+        buffer: *mut hb_buffer_t,
+        text: *const c_char,
+        text_length: c_int,
+        item_offset: c_uint,
+        item_length: c_int,
+    );
+    pub fn hb_buffer_set_direction(buffer: *mut hb_buffer_t, direction: hb_direction_t);
+    pub fn hb_buffer_get_direction(buffer: *mut hb_buffer_t) -> hb_direction_t;
+    pub fn hb_buffer_set_script(buffer: *mut hb_buffer_t, script: hb_script_t);
+    pub fn hb_buffer_set_language(buffer: *mut hb_buffer_t, language: hb_language_t);
+    pub fn hb_buffer_guess_segment_properties(buffer: *mut hb_buffer_t);
+    pub fn hb_buffer_get_glyph_infos(
+        buffer: *mut hb_buffer_t,
+        length: *mut c_uint,
+    ) -> *mut hb_glyph_info_t;
+    pub fn hb_buffer_get_glyph_positions(
+        buffer: *mut hb_buffer_t,
+        length: *mut c_uint,
+    ) -> *mut hb_glyph_position_t;
+
+    pub fn hb_shape(
+        font: *mut hb_font_t,
+        buffer: *mut hb_buffer_t,
+        features: *const hb_feature_t,
+        num_features: c_uint,
+    );
+    pub fn hb_feature_from_string(
+        str: *const c_char,
+        len: c_int,
+        feature: *mut hb_feature_t,
+    ) -> hb_bool_t;
+    pub fn hb_language_from_string(str: *const c_char, len: c_int) -> hb_language_t;
+    pub fn hb_script_from_string(str: *const c_char, len: c_int) -> hb_script_t;
+}
@@ -7,6 +7,10 @@
 
 extern crate libc;
 
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+
 #[cfg(feature = "use_glib")]
 extern crate glib_sys as glib_ffi;
 
@@ -29,7 +33,7 @@ pub mod winapi {
     pub struct HDC(c_void);
 }
 
-use libc::{c_char, c_double, c_int, c_uchar, c_uint, c_ulong, c_void};
+use libc::{c_char, c_double, c_int, c_long, c_uchar, c_uint, c_ulong, c_void};
 
 #[cfg(any(feature = "xlib", feature = "dox"))]
 use x11::xlib;
@@ -194,6 +198,7 @@ debug_impl!(cairo_font_options_t);
 
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FontExtents {
     pub ascent: c_double,
     pub descent: c_double,
@@ -203,6 +208,7 @@ pub struct FontExtents {
 }
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Glyph {
     pub index: c_ulong,
     pub x: c_double,
@@ -210,12 +216,14 @@ pub struct Glyph {
 }
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TextCluster {
     pub num_bytes: c_int,
     pub num_glyphs: c_int,
 }
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TextExtents {
     pub x_bearing: c_double,
     pub y_bearing: c_double,
@@ -278,7 +286,61 @@ pub type cairo_write_func_t =
 #[cfg(any(feature = "freetype", feature = "dox"))]
 pub type FT_Face = *mut c_void;
 #[cfg(any(feature = "freetype", feature = "dox"))]
+pub type FT_Library = *mut c_void;
+#[cfg(any(feature = "freetype", feature = "dox"))]
 pub type FcPattern = c_void;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub type FcConfig = c_void;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub type FcBool = c_int;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub type FcChar8 = c_uchar;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub type FcResult = c_int;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub type FcMatchKind = c_int;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub type FcObjectSet = c_void;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub type FcLangSet = c_void;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub type FcCharSet = c_void;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub type FcChar32 = c_uint;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub type FcLangResult = c_int;
+
+#[cfg(any(feature = "freetype", feature = "dox"))]
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FcFontSet {
+    pub nfont: c_int,
+    pub sfont: c_int,
+    pub fonts: *mut *mut FcPattern,
+}
+
+#[cfg(any(feature = "freetype", feature = "dox"))]
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FT_Var_Axis {
+    pub name: *mut c_char,
+    pub minimum: c_long,
+    pub def: c_long,
+    pub maximum: c_long,
+    pub tag: c_ulong,
+    pub strid: c_uint,
+}
+
+#[cfg(any(feature = "freetype", feature = "dox"))]
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FT_MM_Var {
+    pub num_axis: c_uint,
+    pub num_designs: c_uint,
+    pub num_namedstyles: c_uint,
+    pub axis: *mut FT_Var_Axis,
+    pub namedstyle: *mut c_void,
+}
 
 extern "C" {
     // CAIRO CONTEXT
@@ -286,6 +348,13 @@ extern "C" {
     pub fn cairo_reference(cr: *mut cairo_t) -> *mut cairo_t;
     pub fn cairo_destroy(cr: *mut cairo_t);
     pub fn cairo_status(cr: *mut cairo_t) -> cairo_status_t;
+    pub fn cairo_get_user_data(cr: *mut cairo_t, key: *const cairo_user_data_key_t) -> *mut c_void;
+    pub fn cairo_set_user_data(
+        cr: *mut cairo_t,
+        key: *const cairo_user_data_key_t,
+        user_data: *mut c_void,
+        destroy: cairo_destroy_func_t,
+    ) -> cairo_status_t;
     pub fn cairo_save(cr: *mut cairo_t);
     pub fn cairo_restore(cr: *mut cairo_t);
     pub fn cairo_get_target(cr: *mut cairo_t) -> *mut cairo_surface_t;
@@ -756,7 +825,7 @@ extern "C" {
     pub fn cairo_ft_font_face_create_for_pattern(pattern: *mut FcPattern)
         -> *mut cairo_font_face_t;
     #[cfg(any(feature = "freetype", feature = "dox"))]
-    pub fn cairo_ft_font_option_substitute(
+    pub fn cairo_ft_font_options_substitute(
         options: *const cairo_font_options_t,
         pattern: *mut FcPattern,
     );
@@ -779,6 +848,96 @@ extern "C" {
         synth_flags: cairo_ft_synthesize_t,
     );
 
+    // FONTCONFIG
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FcPatternCreate() -> *mut FcPattern;
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FcPatternDestroy(p: *mut FcPattern);
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FcPatternAddString(
+        p: *mut FcPattern,
+        object: *const c_char,
+        s: *const FcChar8,
+    ) -> FcBool;
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FcPatternAddInteger(p: *mut FcPattern, object: *const c_char, i: c_int) -> FcBool;
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FcConfigSubstitute(
+        config: *mut FcConfig,
+        p: *mut FcPattern,
+        kind: FcMatchKind,
+    ) -> FcBool;
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FcDefaultSubstitute(pattern: *mut FcPattern);
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FcFontMatch(
+        config: *mut FcConfig,
+        p: *mut FcPattern,
+        result: *mut FcResult,
+    ) -> *mut FcPattern;
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FcWeightFromOpenType(ot_weight: c_int) -> c_int;
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FcWeightToOpenType(fc_weight: c_int) -> c_int;
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FcPatternGetString(
+        p: *const FcPattern,
+        object: *const c_char,
+        n: c_int,
+        s: *mut *mut FcChar8,
+    ) -> FcResult;
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FcPatternGetInteger(
+        p: *const FcPattern,
+        object: *const c_char,
+        n: c_int,
+        i: *mut c_int,
+    ) -> FcResult;
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FcPatternGetLangSet(
+        p: *const FcPattern,
+        object: *const c_char,
+        n: c_int,
+        ls: *mut *mut FcLangSet,
+    ) -> FcResult;
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FcPatternGetCharSet(
+        p: *const FcPattern,
+        object: *const c_char,
+        n: c_int,
+        c: *mut *mut FcCharSet,
+    ) -> FcResult;
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FcLangSetHasLang(ls: *const FcLangSet, lang: *const FcChar8) -> FcLangResult;
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FcCharSetHasChar(fcs: *const FcCharSet, ucs4: FcChar32) -> FcBool;
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FcObjectSetCreate() -> *mut FcObjectSet;
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FcObjectSetAdd(os: *mut FcObjectSet, object: *const c_char) -> FcBool;
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FcObjectSetDestroy(os: *mut FcObjectSet);
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FcFontList(
+        config: *mut FcConfig,
+        p: *mut FcPattern,
+        os: *mut FcObjectSet,
+    ) -> *mut FcFontSet;
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FcFontSetDestroy(s: *mut FcFontSet);
+
+    // FREETYPE
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FT_Get_MM_Var(face: FT_Face, amaster: *mut *mut FT_MM_Var) -> c_int;
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FT_Done_MM_Var(library: FT_Library, amaster: *mut FT_MM_Var) -> c_int;
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn FT_Get_Var_Blend_Coordinates(
+        face: FT_Face,
+        num_coords: c_uint,
+        coords: *mut c_long,
+    ) -> c_int;
+
     // CAIRO RASTER
     //pub fn cairo_pattern_create_raster_source(user_data: *mut void, content: Content, width: c_int, height: c_int) -> *mut cairo_pattern_t;
     //pub fn cairo_raster_source_pattern_set_callback_data(pattern: *mut cairo_pattern_t, data: *mut void);
@@ -1459,6 +1618,9 @@ extern "C" {
 #[cfg(feature = "use_glib")]
 pub mod gobject;
 
+#[cfg(any(feature = "harfbuzz", feature = "dox"))]
+pub mod harfbuzz;
+
 pub const STATUS_SUCCESS: i32 = 0;
 pub const STATUS_NO_MEMORY: i32 = 1;
 pub const STATUS_INVALID_RESTORE: i32 = 2;
@@ -1661,6 +1823,44 @@ pub const MESH_CORNER_MESH_CORNER2: u32 = 2;
 pub const MESH_CORNER_MESH_CORNER3: u32 = 3;
 pub const CAIRO_FT_SYNTHESIZE_BOLD: u32 = 1;
 pub const CAIRO_FT_SYNTHESIZE_OBLIQUE: u32 = 2;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub const FC_MATCH_PATTERN: i32 = 0;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub const FC_RESULT_MATCH: i32 = 0;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub const FC_SLANT_ROMAN: i32 = 0;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub const FC_SLANT_ITALIC: i32 = 100;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub const FC_SLANT_OBLIQUE: i32 = 110;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub const FC_FAMILY: &[u8] = b"family\0";
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub const FC_STYLE: &[u8] = b"style\0";
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub const FC_SLANT: &[u8] = b"slant\0";
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub const FC_WEIGHT: &[u8] = b"weight\0";
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub const FC_WIDTH: &[u8] = b"width\0";
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub const FC_LANG: &[u8] = b"lang\0";
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub const FC_FONT_FEATURES: &[u8] = b"fontfeatures\0";
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub const FC_FILE: &[u8] = b"file\0";
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub const FC_INDEX: &[u8] = b"index\0";
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub const FC_CHARSET: &[u8] = b"charset\0";
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub const FC_FULLNAME: &[u8] = b"fullname\0";
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub const FC_LANG_EQUAL: i32 = 0;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub const FC_LANG_DIFFERENT_COUNTRY: i32 = 1;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub const FC_LANG_DIFFERENT_LANG: i32 = 2;
 pub const CAIRO_SCRIPT_MODE_ASCII: i32 = 0;
 pub const CAIRO_SCRIPT_MODE_BINARY: i32 = 1;
 
@@ -0,0 +1,621 @@
+// Copyright 2020, The Gtk-rs Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <https://opensource.org/licenses/MIT>
+
+use std::f64::consts::PI;
+use std::str::FromStr;
+
+use error::Error;
+
+/// A color in the sRGB color space, with components from 0 to 1 and straight alpha.
+///
+/// Colors can be parsed from CSS color syntax: `#rgb`, `#rgba`, `#rrggbb` and `#rrggbbaa`
+/// hex colors, `rgb()`, `rgba()`, `hsl()` and `hsla()` functions, named colors and
+/// `transparent`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub struct Rgba {
+    pub red: f64,
+    pub green: f64,
+    pub blue: f64,
+    pub alpha: f64,
+}
+
+impl Rgba {
+    pub const fn new(red: f64, green: f64, blue: f64, alpha: f64) -> Rgba {
+        Rgba {
+            red,
+            green,
+            blue,
+            alpha,
+        }
+    }
+
+    /// Creates an opaque color.
+    pub const fn rgb(red: f64, green: f64, blue: f64) -> Rgba {
+        Rgba::new(red, green, blue, 1.0)
+    }
+
+    pub fn from_rgba8(red: u8, green: u8, blue: u8, alpha: u8) -> Rgba {
+        Rgba::new(
+            f64::from(red) / 255.0,
+            f64::from(green) / 255.0,
+            f64::from(blue) / 255.0,
+            f64::from(alpha) / 255.0,
+        )
+    }
+
+    /// Returns the components scaled to 0..=255 and rounded.
+    pub fn to_rgba8(&self) -> (u8, u8, u8, u8) {
+        let scale = |c: f64| (clamp(c) * 255.0).round() as u8;
+        (
+            scale(self.red),
+            scale(self.green),
+            scale(self.blue),
+            scale(self.alpha),
+        )
+    }
+
+    /// Returns the color as `#rrggbb`, or `#rrggbbaa` if it is not opaque.
+    pub fn to_hex(&self) -> String {
+        let (red, green, blue, alpha) = self.to_rgba8();
+        if alpha == 255 {
+            format!("#{:02x}{:02x}{:02x}", red, green, blue)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", red, green, blue, alpha)
+        }
+    }
+
+    pub fn with_alpha(&self, alpha: f64) -> Rgba {
+        Rgba { alpha, ..*self }
+    }
+
+    /// Creates a color from a hue in degrees, and a saturation and lightness from 0 to 1.
+    pub fn from_hsl(hue: f64, saturation: f64, lightness: f64, alpha: f64) -> Rgba {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        from_hue(hue, chroma, lightness - chroma / 2.0, alpha)
+    }
+
+    /// Returns the hue in degrees from 0 to 360, and the saturation and lightness from 0 to 1.
+    pub fn to_hsl(&self) -> (f64, f64, f64) {
+        let (hue, max, min) = self.hue();
+        let lightness = (max + min) / 2.0;
+        let saturation = if max == min {
+            0.0
+        } else {
+            (max - min) / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+        (hue, saturation, lightness)
+    }
+
+    /// Creates a color from a hue in degrees, and a saturation and value from 0 to 1.
+    pub fn from_hsv(hue: f64, saturation: f64, value: f64, alpha: f64) -> Rgba {
+        let chroma = value * saturation;
+        from_hue(hue, chroma, value - chroma, alpha)
+    }
+
+    /// Returns the hue in degrees from 0 to 360, and the saturation and value from 0 to 1.
+    pub fn to_hsv(&self) -> (f64, f64, f64) {
+        let (hue, max, min) = self.hue();
+        let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+        (hue, saturation, max)
+    }
+
+    /// Creates a color from CIE L\*a\*b\* coordinates with a D65 white point, where the
+    /// lightness goes from 0 to 100. Colors outside of the sRGB gamut are clamped.
+    pub fn from_lab(lightness: f64, a: f64, b: f64, alpha: f64) -> Rgba {
+        let fy = (lightness + 16.0) / 116.0;
+        let x = WHITE_X * lab_f_inv(fy + a / 500.0);
+        let y = lab_f_inv(fy);
+        let z = WHITE_Z * lab_f_inv(fy - b / 200.0);
+        Rgba::new(
+            srgb_encode(3.240_454_2 * x - 1.537_138_5 * y - 0.498_531_4 * z),
+            srgb_encode(-0.969_266 * x + 1.876_010_8 * y + 0.041_556 * z),
+            srgb_encode(0.055_643_4 * x - 0.204_025_9 * y + 1.057_225_2 * z),
+            alpha,
+        )
+    }
+
+    /// Returns the CIE L\*a\*b\* coordinates of the color with a D65 white point.
+    pub fn to_lab(&self) -> (f64, f64, f64) {
+        let (r, g, b) = (
+            srgb_decode(self.red),
+            srgb_decode(self.green),
+            srgb_decode(self.blue),
+        );
+        let x = 0.412_456_4 * r + 0.357_576_1 * g + 0.180_437_5 * b;
+        let y = 0.212_672_9 * r + 0.715_152_2 * g + 0.072_175 * b;
+        let z = 0.019_333_9 * r + 0.119_192 * g + 0.950_304_1 * b;
+        let (fx, fy, fz) = (lab_f(x / WHITE_X), lab_f(y), lab_f(z / WHITE_Z));
+        (116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz))
+    }
+
+    /// Raises the HSL lightness by `amount`, from 0 to 1.
+    pub fn lighten(&self, amount: f64) -> Rgba {
+        let (hue, saturation, lightness) = self.to_hsl();
+        Rgba::from_hsl(hue, saturation, clamp(lightness + amount), self.alpha)
+    }
+
+    /// Lowers the HSL lightness by `amount`, from 0 to 1.
+    pub fn darken(&self, amount: f64) -> Rgba {
+        self.lighten(-amount)
+    }
+
+    /// Interpolates the components between `self`, for a `t` of 0, and `other`, for 1.
+    pub fn mix(&self, other: &Rgba, t: f64) -> Rgba {
+        let lerp = |a: f64, b: f64| a + (b - a) * t;
+        Rgba::new(
+            lerp(self.red, other.red),
+            lerp(self.green, other.green),
+            lerp(self.blue, other.blue),
+            lerp(self.alpha, other.alpha),
+        )
+    }
+
+    // Returns the hue in degrees with the largest and smallest components.
+    fn hue(&self) -> (f64, f64, f64) {
+        let (r, g, b) = (self.red, self.green, self.blue);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        (hue, max, min)
+    }
+}
+
+impl FromStr for Rgba {
+    type Err = Error;
+
+    /// Parses a CSS color, returning `Error::InvalidString` if it is malformed or unknown.
+    fn from_str(s: &str) -> Result<Rgba, Error> {
+        let s = s.trim().to_ascii_lowercase();
+        let mut chars = s.chars();
+        if chars.next() == Some('#') {
+            return parse_hex(chars.as_str());
+        }
+        if let Some(open) = s.find('(') {
+            if !s.ends_with(')') {
+                return Err(Error::InvalidString);
+            }
+            let args: Vec<&str> = s[open + 1..s.len() - 1]
+                .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+                .filter(|arg| !arg.is_empty())
+                .collect();
+            if args.len() != 3 && args.len() != 4 {
+                return Err(Error::InvalidString);
+            }
+            let alpha = match args.get(3) {
+                Some(alpha) => clamp(parse_number(alpha, 1.0)?),
+                None => 1.0,
+            };
+            return match s[..open].trim_end() {
+                "rgb" | "rgba" => {
+                    let channel = |arg: &str| parse_number(arg, 255.0).map(|c| clamp(c / 255.0));
+                    Ok(Rgba::new(
+                        channel(args[0])?,
+                        channel(args[1])?,
+                        channel(args[2])?,
+                        alpha,
+                    ))
+                }
+                "hsl" | "hsla" => {
+                    if !args[1].ends_with('%') || !args[2].ends_with('%') {
+                        return Err(Error::InvalidString);
+                    }
+                    Ok(Rgba::from_hsl(
+                        parse_hue(args[0])?,
+                        clamp(parse_number(args[1], 1.0)?),
+                        clamp(parse_number(args[2], 1.0)?),
+                        alpha,
+                    ))
+                }
+                _ => Err(Error::InvalidString),
+            };
+        }
+        if s == "transparent" {
+            return Ok(Rgba::new(0.0, 0.0, 0.0, 0.0));
+        }
+        NAMED_COLORS
+            .binary_search_by(|&(name, _)| name.cmp(&s))
+            .map(|i| {
+                let value = NAMED_COLORS[i].1;
+                Rgba::from_rgba8((value >> 16) as u8, (value >> 8) as u8, value as u8, 255)
+            })
+            .map_err(|_| Error::InvalidString)
+    }
+}
+
+impl From<(f64, f64, f64, f64)> for Rgba {
+    fn from((red, green, blue, alpha): (f64, f64, f64, f64)) -> Rgba {
+        Rgba::new(red, green, blue, alpha)
+    }
+}
+
+impl From<Rgba> for (f64, f64, f64, f64) {
+    fn from(color: Rgba) -> (f64, f64, f64, f64) {
+        (color.red, color.green, color.blue, color.alpha)
+    }
+}
+
+const WHITE_X: f64 = 0.950_47;
+const WHITE_Z: f64 = 1.088_83;
+
+// `f64::clamp` needs Rust 1.50.
+#[allow(clippy::manual_clamp)]
+fn clamp(value: f64) -> f64 {
+    value.max(0.0).min(1.0)
+}
+
+fn from_hue(hue: f64, chroma: f64, m: f64, alpha: f64) -> Rgba {
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    Rgba::new(r + m, g + m, b + m, alpha)
+}
+
+fn srgb_decode(c: f64) -> f64 {
+    if c <= 0.040_45 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn srgb_encode(c: f64) -> f64 {
+    let c = if c <= 0.003_130_8 {
+        12.92 * c
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    clamp(c)
+}
+
+const LAB_DELTA: f64 = 6.0 / 29.0;
+
+fn lab_f(t: f64) -> f64 {
+    if t > LAB_DELTA.powi(3) {
+        t.cbrt()
+    } else {
+        t / (3.0 * LAB_DELTA * LAB_DELTA) + 4.0 / 29.0
+    }
+}
+
+fn lab_f_inv(t: f64) -> f64 {
+    if t > LAB_DELTA {
+        t.powi(3)
+    } else {
+        3.0 * LAB_DELTA * LAB_DELTA * (t - 4.0 / 29.0)
+    }
+}
+
+fn parse_hex(digits: &str) -> Result<Rgba, Error> {
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(Error::InvalidString);
+    }
+    let width = match digits.len() {
+        3 | 4 => 1,
+        6 | 8 => 2,
+        _ => return Err(Error::InvalidString),
+    };
+    let channel = |i: usize| {
+        let value = u8::from_str_radix(&digits[i * width..(i + 1) * width], 16).unwrap();
+        if width == 1 {
+            value * 17
+        } else {
+            value
+        }
+    };
+    let alpha = if digits.len() == 4 * width {
+        channel(3)
+    } else {
+        255
+    };
+    Ok(Rgba::from_rgba8(channel(0), channel(1), channel(2), alpha))
+}
+
+// Parses a number, or a percentage of `full`.
+fn parse_number(arg: &str, full: f64) -> Result<f64, Error> {
+    let number = arg.trim_end_matches('%');
+    let percentage = match arg.len() - number.len() {
+        0 => false,
+        1 => true,
+        _ => return Err(Error::InvalidString),
+    };
+    match number.parse::<f64>() {
+        Ok(value) if value.is_finite() && percentage => Ok(value / 100.0 * full),
+        Ok(value) if value.is_finite() => Ok(value),
+        _ => Err(Error::InvalidString),
+    }
+}
+
+// Parses a CSS angle in degrees.
+fn parse_hue(arg: &str) -> Result<f64, Error> {
+    let units = [
+        ("deg", 1.0),
+        ("grad", 0.9),
+        ("rad", 180.0 / PI),
+        ("turn", 360.0),
+    ];
+    let (number, scale) = units
+        .iter()
+        .find(|&&(unit, _)| arg.ends_with(unit))
+        .map_or((arg, 1.0), |&(unit, scale)| {
+            (&arg[..arg.len() - unit.len()], scale)
+        });
+    parse_number(number, 1.0).map(|hue| hue * scale)
+}
+
+// CSS named colors, sorted by name.
+static NAMED_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Rgba, b: Rgba) {
+        assert!(
+            (a.red - b.red).abs() < 1e-6
+                && (a.green - b.green).abs() < 1e-6
+                && (a.blue - b.blue).abs() < 1e-6
+                && (a.alpha - b.alpha).abs() < 1e-6,
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn parse_hex_colors() {
+        assert_eq!("#f00".parse(), Ok(Rgba::rgb(1.0, 0.0, 0.0)));
+        assert_eq!("#00ff0080".parse::<Rgba>().unwrap().alpha, 128.0 / 255.0);
+        assert_eq!("#0000FF".parse::<Rgba>().unwrap().to_hex(), "#0000ff");
+        assert_eq!("#0008".parse::<Rgba>().unwrap().to_hex(), "#00000088");
+        for invalid in &["ff0000", "#ff000", "#ggg", "#"] {
+            assert!(invalid.parse::<Rgba>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn parse_functions_and_names() {
+        assert_eq!("rgb(255, 0, 0)".parse(), Ok(Rgba::rgb(1.0, 0.0, 0.0)));
+        assert_eq!(
+            "rgba(0 100% 0 / 50%)".parse(),
+            Ok(Rgba::new(0.0, 1.0, 0.0, 0.5))
+        );
+        assert_close(
+            "hsl(120, 100%, 25%)".parse().unwrap(),
+            Rgba::rgb(0.0, 0.5, 0.0),
+        );
+        assert_close(
+            "hsla(0.5turn 100% 50% / 0.2)".parse().unwrap(),
+            Rgba::new(0.0, 1.0, 1.0, 0.2),
+        );
+        assert_eq!(
+            " RebeccaPurple ".parse::<Rgba>().unwrap().to_hex(),
+            "#663399"
+        );
+        assert_eq!("transparent".parse::<Rgba>().unwrap().alpha, 0.0);
+        for invalid in &[
+            "rgb(1, 2)",
+            "rgb(1, 2, 3",
+            "hsl(0, 1, 1)",
+            "cmyk(0, 0, 0, 0)",
+            "blurple",
+        ] {
+            assert!(invalid.parse::<Rgba>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn named_colors_are_sorted() {
+        assert!(NAMED_COLORS.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn hsl_and_hsv() {
+        let color = Rgba::from_rgba8(51, 102, 153, 255);
+        let (h, s, l) = color.to_hsl();
+        assert!((h - 210.0).abs() < 1e-9 && (s - 0.5).abs() < 1e-9 && (l - 0.4).abs() < 1e-9);
+        assert_close(Rgba::from_hsl(h, s, l, 1.0), color);
+
+        let (h, s, v) = color.to_hsv();
+        assert!((h - 210.0).abs() < 1e-9 && (s - 2.0 / 3.0).abs() < 1e-9 && (v - 0.6).abs() < 1e-9);
+        assert_close(Rgba::from_hsv(h, s, v, 1.0), color);
+
+        assert_eq!(Rgba::rgb(0.5, 0.5, 0.5).to_hsl(), (0.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn lab() {
+        let (l, a, b) = Rgba::rgb(1.0, 1.0, 1.0).to_lab();
+        assert!((l - 100.0).abs() < 1e-3 && a.abs() < 1e-3 && b.abs() < 1e-3);
+        let (l, a, b) = Rgba::rgb(1.0, 0.0, 0.0).to_lab();
+        assert!((l - 53.24).abs() < 0.01 && (a - 80.09).abs() < 0.01 && (b - 67.20).abs() < 0.01);
+
+        let color = Rgba::new(0.2, 0.7, 0.4, 0.5);
+        let (l, a, b) = color.to_lab();
+        let back = Rgba::from_lab(l, a, b, 0.5);
+        assert!((back.red - 0.2).abs() < 1e-5 && (back.blue - 0.4).abs() < 1e-5);
+    }
+
+    #[test]
+    fn adjustments() {
+        let red = Rgba::rgb(1.0, 0.0, 0.0);
+        assert_close(red.lighten(0.25), Rgba::rgb(1.0, 0.5, 0.5));
+        assert_close(red.darken(0.25), Rgba::rgb(0.5, 0.0, 0.0));
+        assert_close(red.darken(1.0), Rgba::rgb(0.0, 0.0, 0.0));
+        assert_close(
+            red.mix(&Rgba::new(0.0, 0.0, 1.0, 0.0), 0.25),
+            Rgba::new(0.75, 0.0, 0.25, 0.75),
+        );
+        assert_eq!(red.with_alpha(0.5).alpha, 0.5);
+    }
+}
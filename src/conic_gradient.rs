@@ -0,0 +1,284 @@
+// Copyright 2020, The Gtk-rs Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <https://opensource.org/licenses/MIT>
+
+use std::f64::consts::PI;
+
+use enums::MeshCorner;
+use patterns::Mesh;
+use Rgba;
+
+// Patches in a full turn, for colors to follow the angle closely.
+const PATCHES_PER_TURN: f64 = 32.0;
+
+/// A gradient whose color changes with the angle around a center, like CSS
+/// `conic-gradient()`.
+///
+/// Cairo has no conic gradients, so they are drawn with the wedge-shaped patches of the `Mesh`
+/// returned by `to_mesh`. Offsets go once around the center from `start_angle`, clockwise in
+/// the default y-down user space. The first and last colors meet at the start angle without
+/// blending, as in CSS.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", from = "ConicGradientData")
+)]
+pub struct ConicGradient {
+    cx: f64,
+    cy: f64,
+    start_angle: f64,
+    stops: Vec<(f64, Rgba)>,
+}
+
+// Deserialized gradients are rebuilt with `add_color_stop`, for stops to be clamped and
+// sorted.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(crate = "serde_crate")]
+struct ConicGradientData {
+    cx: f64,
+    cy: f64,
+    start_angle: f64,
+    stops: Vec<(f64, Rgba)>,
+}
+
+#[cfg(feature = "serde")]
+impl From<ConicGradientData> for ConicGradient {
+    fn from(data: ConicGradientData) -> ConicGradient {
+        let mut gradient = ConicGradient::new(data.cx, data.cy, data.start_angle);
+        for (offset, color) in data.stops {
+            gradient.add_color_stop(offset, color);
+        }
+        gradient
+    }
+}
+
+impl ConicGradient {
+    /// Creates a gradient without stops around (`cx`, `cy`). `start_angle` is in radians from
+    /// the positive x axis towards the positive y axis, like the angles of `Context::arc`.
+    pub fn new(cx: f64, cy: f64, start_angle: f64) -> ConicGradient {
+        ConicGradient {
+            cx,
+            cy,
+            start_angle,
+            stops: Vec::new(),
+        }
+    }
+
+    pub fn add_color_stop_rgb(&mut self, offset: f64, red: f64, green: f64, blue: f64) {
+        self.add_color_stop(offset, Rgba::rgb(red, green, blue))
+    }
+
+    pub fn add_color_stop_rgba(
+        &mut self,
+        offset: f64,
+        red: f64,
+        green: f64,
+        blue: f64,
+        alpha: f64,
+    ) {
+        self.add_color_stop(offset, Rgba::new(red, green, blue, alpha))
+    }
+
+    /// Adds a stop at `offset`, clamped to 0..=1. Like with `Gradient`, a stop added at the
+    /// offset of another one goes after it, which makes a sharp transition.
+    // `f64::clamp` needs Rust 1.50.
+    #[allow(clippy::manual_clamp)]
+    pub fn add_color_stop(&mut self, offset: f64, color: Rgba) {
+        let offset = offset.max(0.0).min(1.0);
+        let index = self
+            .stops
+            .iter()
+            .position(|&(stop, _)| stop > offset)
+            .unwrap_or(self.stops.len());
+        self.stops.insert(index, (offset, color));
+    }
+
+    pub fn get_center(&self) -> (f64, f64) {
+        (self.cx, self.cy)
+    }
+
+    pub fn get_start_angle(&self) -> f64 {
+        self.start_angle
+    }
+
+    /// Returns the offsets and colors of the stops, sorted by offset.
+    pub fn get_color_stops(&self) -> &[(f64, Rgba)] {
+        &self.stops
+    }
+
+    /// Builds a mesh covering the disc of `radius` around the center.
+    ///
+    /// The mesh is transparent outside of the disc, so `radius` should reach the corners of
+    /// the area to paint. Without stops, the mesh has no patches and paints nothing.
+    pub fn to_mesh(&self, radius: f64) -> Mesh {
+        let mesh = Mesh::new();
+        for (t0, c0, t1, c1) in self.segments() {
+            let patches = ((t1 - t0) * PATCHES_PER_TURN).ceil().max(1.0);
+            for i in 0..patches as usize {
+                let (a, b) = (i as f64 / patches, (i + 1) as f64 / patches);
+                self.add_wedge(
+                    &mesh,
+                    radius,
+                    (t0 + (t1 - t0) * a, c0.mix(&c1, a)),
+                    (t0 + (t1 - t0) * b, c0.mix(&c1, b)),
+                );
+            }
+        }
+        mesh
+    }
+
+    // Returns the spans of offsets between stops that cover 0..1, with the colors at their
+    // ends. Spans before the first and after the last stop have a single color.
+    fn segments(&self) -> Vec<(f64, Rgba, f64, Rgba)> {
+        let (first, last) = match (self.stops.first(), self.stops.last()) {
+            (Some(&(_, first)), Some(&(_, last))) => (first, last),
+            _ => return Vec::new(),
+        };
+
+        let mut stops = Vec::with_capacity(self.stops.len() + 2);
+        stops.push((0.0, first));
+        stops.extend_from_slice(&self.stops);
+        stops.push((1.0, last));
+        stops
+            .windows(2)
+            .filter(|w| w[1].0 > w[0].0)
+            .map(|w| (w[0].0, w[0].1, w[1].0, w[1].1))
+            .collect()
+    }
+
+    // Adds a patch between the offsets of `from` and `to`, with two corners at the center and
+    // a Bézier arc as the outer side.
+    fn add_wedge(&self, mesh: &Mesh, radius: f64, from: (f64, Rgba), to: (f64, Rgba)) {
+        let a0 = self.start_angle + from.0 * 2.0 * PI;
+        let a1 = self.start_angle + to.0 * 2.0 * PI;
+        let k = 4.0 / 3.0 * ((a1 - a0) / 4.0).tan() * radius;
+        let (x0, y0) = (self.cx + radius * a0.cos(), self.cy + radius * a0.sin());
+        let (x3, y3) = (self.cx + radius * a1.cos(), self.cy + radius * a1.sin());
+
+        mesh.begin_patch();
+        mesh.move_to(self.cx, self.cy);
+        mesh.line_to(x0, y0);
+        mesh.curve_to(
+            x0 - k * a0.sin(),
+            y0 + k * a0.cos(),
+            x3 + k * a1.sin(),
+            y3 - k * a1.cos(),
+            x3,
+            y3,
+        );
+        mesh.line_to(self.cx, self.cy);
+        mesh.set_corner_color(MeshCorner::MeshCorner0, from.1);
+        mesh.set_corner_color(MeshCorner::MeshCorner1, from.1);
+        mesh.set_corner_color(MeshCorner::MeshCorner2, to.1);
+        mesh.set_corner_color(MeshCorner::MeshCorner3, to.1);
+        mesh.end_patch();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use context::Context;
+    use enums::Format;
+    use image_surface::ImageSurface;
+    use paths::PathSegment;
+
+    fn pixel(surface: &mut ImageSurface, x: usize, y: usize) -> (u8, u8, u8) {
+        let stride = surface.get_stride() as usize;
+        let data = surface.get_data().unwrap();
+        let i = y * stride + x * 4;
+        let value = u32::from_ne_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+        ((value >> 16) as u8, (value >> 8) as u8, value as u8)
+    }
+
+    fn assert_near(actual: (u8, u8, u8), expected: (u8, u8, u8)) {
+        let near = |a: u8, b: u8| (i32::from(a) - i32::from(b)).abs() <= 4;
+        assert!(
+            near(actual.0, expected.0) && near(actual.1, expected.1) && near(actual.2, expected.2),
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn stops_are_sorted() {
+        let mut gradient = ConicGradient::new(0.0, 0.0, 0.0);
+        gradient.add_color_stop_rgb(0.5, 1.0, 0.0, 0.0);
+        gradient.add_color_stop_rgb(2.0, 0.0, 1.0, 0.0);
+        gradient.add_color_stop_rgb(0.5, 0.0, 0.0, 1.0);
+        gradient.add_color_stop_rgb(0.2, 0.0, 0.0, 0.0);
+        let offsets: Vec<f64> = gradient.get_color_stops().iter().map(|s| s.0).collect();
+        assert_eq!(offsets, [0.2, 0.5, 0.5, 1.0]);
+        assert_eq!(gradient.get_color_stops()[2].1, Rgba::rgb(0.0, 0.0, 1.0));
+
+        // The sharp transition at 0.5 has no patch of its own.
+        let spans: Vec<(f64, f64)> = gradient.segments().iter().map(|s| (s.0, s.2)).collect();
+        assert_eq!(spans, [(0.0, 0.2), (0.2, 0.5), (0.5, 1.0)]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let json = concat!(
+            r#"{"cx":1.0,"cy":2.0,"start_angle":0.5,"stops":["#,
+            r#"[2.0,{"red":1.0,"green":0.0,"blue":0.0,"alpha":1.0}],"#,
+            r#"[0.25,{"red":0.0,"green":0.0,"blue":1.0,"alpha":1.0}]]}"#
+        );
+        let gradient: ConicGradient = ::serde_json::from_str(json).unwrap();
+        assert_eq!(gradient.get_center(), (1.0, 2.0));
+        assert_eq!(gradient.get_start_angle(), 0.5);
+        assert_eq!(
+            gradient.get_color_stops(),
+            [
+                (0.25, Rgba::rgb(0.0, 0.0, 1.0)),
+                (1.0, Rgba::rgb(1.0, 0.0, 0.0))
+            ]
+        );
+        let json = ::serde_json::to_string(&gradient).unwrap();
+        let copy: ConicGradient = ::serde_json::from_str(&json).unwrap();
+        assert_eq!(copy.get_color_stops(), gradient.get_color_stops());
+    }
+
+    #[test]
+    fn patches() {
+        let mut gradient = ConicGradient::new(10.0, 20.0, 0.0);
+        assert_eq!(gradient.to_mesh(5.0).get_patch_count(), 0);
+
+        gradient.add_color_stop_rgb(0.0, 1.0, 0.0, 0.0);
+        gradient.add_color_stop_rgb(1.0, 0.0, 0.0, 1.0);
+        let mesh = gradient.to_mesh(5.0);
+        assert_eq!(mesh.get_patch_count(), 32);
+        let path: Vec<PathSegment> = mesh.get_path(0).iter().collect();
+        assert_eq!(path[0], PathSegment::MoveTo((10.0, 20.0)));
+        match path[1] {
+            PathSegment::CurveTo(_, _, (x, y)) => {
+                assert!((x - 15.0).abs() < 1e-9 && (y - 20.0).abs() < 1e-9)
+            }
+            ref segment => panic!("unexpected {:?}", segment),
+        }
+    }
+
+    #[test]
+    fn colors_follow_angle() {
+        let mut gradient = ConicGradient::new(50.0, 50.0, 0.0);
+        gradient.add_color_stop_rgb(0.0, 1.0, 0.0, 0.0);
+        gradient.add_color_stop_rgb(1.0, 0.0, 0.0, 1.0);
+
+        let mut surface = ImageSurface::create(Format::Rgb24, 100, 100).unwrap();
+        {
+            let cr = Context::new(&surface);
+            cr.set_source(&gradient.to_mesh(80.0));
+            cr.paint();
+        }
+        surface.flush();
+
+        // On both sides of the seam, along the positive x axis.
+        assert_near(pixel(&mut surface, 90, 51), (253, 0, 1));
+        assert_near(pixel(&mut surface, 90, 48), (1, 0, 253));
+        // A quarter turn clockwise.
+        assert_near(pixel(&mut surface, 50, 90), (192, 0, 63));
+    }
+}
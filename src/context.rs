@@ -3,18 +3,26 @@
 // Licensed under the MIT license, see the LICENSE file or <https://opensource.org/licenses/MIT>
 
 use ffi;
-use font::{FontExtents, FontFace, FontOptions, Glyph, ScaledFont, TextCluster, TextExtents};
+use font::{
+    FontExtents, FontFace, FontOptions, Glyph, ScaledFont, TextAlignment, TextCluster,
+    TextDecoration, TextDirection, TextExtents,
+};
 #[cfg(feature = "use_glib")]
 use glib::translate::*;
 use libc::c_int;
 use matrices::Matrix;
-use paths::Path;
+use paths::{Path, PathMeasure};
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::ffi::CString;
 use std::fmt;
 use std::ops;
 use std::ptr;
+use std::rc::Rc;
 use std::slice;
 use Rectangle;
+use Rgba;
 use {
     Antialias, Content, FillRule, FontSlant, FontWeight, LineCap, LineJoin, Operator,
     TextClusterFlags,
@@ -23,7 +31,9 @@ use {
 use error::Error;
 use ffi::{cairo_rectangle_list_t, cairo_t};
 use patterns::Pattern;
+use recording_surface::RecordingSurface;
 use surface::Surface;
+use user_data::UserDataKey;
 use utils::status_to_result;
 
 pub struct RectangleList {
@@ -164,6 +174,14 @@ impl Context {
         unsafe { Self::from_raw_full(ffi::cairo_create(target.to_raw_none())) }
     }
 
+    // Returns a context on a recording surface that is never drawn to. Paths are only built
+    // and measured through a `Context`, so this is where detached ones come from.
+    pub(crate) fn scratch() -> Context {
+        let surface = RecordingSurface::create(Content::Alpha, None)
+            .expect("Failed to create a recording surface");
+        Context::new(&surface)
+    }
+
     pub fn save(&self) {
         unsafe { ffi::cairo_save(self.0.as_ptr()) }
         self.status().expect("Failed to save")
@@ -206,6 +224,10 @@ impl Context {
         unsafe { ffi::cairo_set_source_rgba(self.0.as_ptr(), red, green, blue, alpha) }
     }
 
+    pub fn set_source_color(&self, color: Rgba) {
+        self.set_source_rgba(color.red, color.green, color.blue, color.alpha)
+    }
+
     pub fn set_source(&self, source: &Pattern) {
         unsafe {
             ffi::cairo_set_source(self.0.as_ptr(), source.to_raw_none());
@@ -613,6 +635,185 @@ impl Context {
         }
     }
 
+    /// Shows `text` like `show_text`, after reordering it with the Unicode Bidirectional
+    /// Algorithm. See `ScaledFont::text_to_glyphs_bidi`.
+    ///
+    /// Each directional run is shown with `show_text_glyphs`, so that the text stays
+    /// extractable in logical order from PDF output.
+    pub fn show_text_bidi(&self, text: &str, direction: Option<TextDirection>) {
+        let (x, y) = if self.has_current_point() {
+            self.get_current_point()
+        } else {
+            (0.0, 0.0)
+        };
+        let runs = self
+            .get_scaled_font()
+            .text_to_glyphs_bidi(x, y, text, direction);
+        let mut x_advance = 0.0;
+        for run in &runs {
+            self.show_text_glyphs(
+                &text[run.range.clone()],
+                &run.glyphs,
+                &run.clusters,
+                run.cluster_flags,
+            );
+            x_advance += run.x_advance;
+        }
+        self.move_to(x + x_advance, y);
+    }
+
+    /// Adds the outlines of `text` laid out along `path` to the current path, each glyph
+    /// rotated to follow the path's direction at its middle.
+    ///
+    /// `offset` is the distance between the text and the start of the path for
+    /// `TextAlignment::Left`, the end of the path for `TextAlignment::Right` and both for
+    /// `TextAlignment::Justify`, which spreads the glyphs between them. Centered text is moved
+    /// by `offset` from the middle of the path. Glyphs falling outside of the path are
+    /// skipped.
+    ///
+    /// The result can be filled or stroked like the result of `text_path`.
+    pub fn text_path_on_path(
+        &self,
+        path: &Path,
+        text: &str,
+        offset: f64,
+        alignment: TextAlignment,
+    ) {
+        let current = self.copy_path();
+        self.new_path();
+        self.append_path(path);
+        let measure = PathMeasure::new(&self.copy_path_flat());
+        self.new_path();
+        self.append_path(&current);
+
+        let scaled_font = self.get_scaled_font();
+        let (glyphs, _) = scaled_font.text_to_glyphs(0.0, 0.0, text);
+        let advances: Vec<f64> = glyphs
+            .iter()
+            .map(|glyph| {
+                let glyph = Glyph {
+                    x: 0.0,
+                    y: 0.0,
+                    ..*glyph
+                };
+                scaled_font.glyph_extents(&[glyph]).x_advance
+            })
+            .collect();
+        let width = scaled_font.glyph_extents(&glyphs).x_advance;
+
+        let length = measure.length();
+        let (start, spacing) = match alignment {
+            TextAlignment::Left => (offset, 0.0),
+            TextAlignment::Right => (length - offset - width, 0.0),
+            TextAlignment::Center => ((length - width) / 2.0 + offset, 0.0),
+            TextAlignment::Justify if glyphs.len() > 1 => (
+                offset,
+                (length - 2.0 * offset - width) / (glyphs.len() - 1) as f64,
+            ),
+            TextAlignment::Justify => (offset, 0.0),
+        };
+
+        for (i, (glyph, &advance)) in glyphs.iter().zip(&advances).enumerate() {
+            let middle = start + glyph.x + i as f64 * spacing + advance / 2.0;
+            if let Some((x, y, angle)) = measure.point_at(middle) {
+                self.save();
+                self.translate(x, y);
+                self.rotate(angle);
+                self.glyph_path(&[Glyph {
+                    index: glyph.index,
+                    x: -advance / 2.0,
+                    y: glyph.y,
+                }]);
+                self.restore();
+            }
+        }
+    }
+
+    /// Fills `text` laid out along `path` with the current source, leaving the current path
+    /// unchanged. See `text_path_on_path`.
+    pub fn show_text_on_path(
+        &self,
+        path: &Path,
+        text: &str,
+        offset: f64,
+        alignment: TextAlignment,
+    ) {
+        let current = self.copy_path();
+        self.new_path();
+        self.text_path_on_path(path, text, offset, alignment);
+        self.fill();
+        self.append_path(&current);
+    }
+
+    /// Draws a `decoration` line for `glyphs` with the current source, using the decoration
+    /// metrics of the current font and leaving the current path unchanged.
+    ///
+    /// The line spans the advance of the glyphs from the origin of the first one, and is
+    /// transformed with the font matrix and the CTM like the glyphs. With `skip_descenders`,
+    /// underlines are interrupted around glyphs whose ink crosses them.
+    pub fn show_text_decoration(
+        &self,
+        glyphs: &[Glyph],
+        decoration: TextDecoration,
+        skip_descenders: bool,
+    ) {
+        let first = match glyphs.first() {
+            Some(glyph) => *glyph,
+            None => return,
+        };
+        let scaled_font = self.get_scaled_font();
+        let font_matrix = self.get_font_matrix();
+        let to_font_space = match font_matrix.try_invert() {
+            Ok(matrix) => matrix,
+            Err(_) => return,
+        };
+
+        let (position, thickness) = scaled_font.get_decoration_metrics().get(decoration);
+        let (top, bottom) = (position - thickness / 2.0, position + thickness / 2.0);
+        let extents = scaled_font.glyph_extents(glyphs);
+        let (length, _) = to_font_space.transform_distance(extents.x_advance, extents.y_advance);
+
+        let mut spans = vec![(0.0, length)];
+        if skip_descenders && decoration == TextDecoration::Underline {
+            for glyph in glyphs {
+                let ink = scaled_font.glyph_extents(&[*glyph]);
+                if ink.width <= 0.0 || ink.height <= 0.0 {
+                    continue;
+                }
+                let (x, y) = (glyph.x - first.x, glyph.y - first.y);
+                let (mut x0, mut y0) = (std::f64::INFINITY, std::f64::INFINITY);
+                let (mut x1, mut y1) = (std::f64::NEG_INFINITY, std::f64::NEG_INFINITY);
+                for &(dx, dy) in &[
+                    (ink.x_bearing, ink.y_bearing),
+                    (ink.x_bearing + ink.width, ink.y_bearing),
+                    (ink.x_bearing, ink.y_bearing + ink.height),
+                    (ink.x_bearing + ink.width, ink.y_bearing + ink.height),
+                ] {
+                    let (fx, fy) = to_font_space.transform_distance(x + dx, y + dy);
+                    x0 = x0.min(fx);
+                    y0 = y0.min(fy);
+                    x1 = x1.max(fx);
+                    y1 = y1.max(fy);
+                }
+                if y1 > top && y0 < bottom {
+                    spans = cut_spans(&spans, x0 - thickness, x1 + thickness);
+                }
+            }
+        }
+
+        let current = self.copy_path();
+        self.new_path();
+        self.save();
+        self.translate(first.x, first.y);
+        self.transform(font_matrix);
+        for &(start, end) in &spans {
+            self.rectangle(start, top, end - start, thickness);
+        }
+        self.restore();
+        self.fill();
+        self.append_path(&current);
+    }
+
     pub fn font_extents(&self) -> FontExtents {
         let mut extents = FontExtents {
             ascent: 0.0,
@@ -782,14 +983,73 @@ impl Context {
             ffi::cairo_tag_end(self.0.as_ptr(), tag_name.as_ptr())
         }
     }
+
+    user_data_methods! {
+        ffi::cairo_get_user_data,
+        ffi::cairo_set_user_data,
+    }
+
+    /// Attaches `value` to the underlying cairo context, replacing the value of the same type
+    /// attached before.
+    ///
+    /// Values are shared by all `Context`s wrapping the same `cairo_t`, including borrowed
+    /// ones, and dropped with it.
+    pub fn set_extension<T: 'static>(&self, value: T) {
+        self.extensions()
+            .borrow_mut()
+            .insert(TypeId::of::<T>(), Rc::new(value));
+    }
+
+    /// Returns the value of type `T` attached with `set_extension`.
+    pub fn get_extension<T: 'static>(&self) -> Option<Rc<T>> {
+        let value = self.extensions().borrow().get(&TypeId::of::<T>())?.clone();
+        value.downcast().ok()
+    }
+
+    /// Detaches the value of type `T` and returns it.
+    pub fn remove_extension<T: 'static>(&self) -> Option<Rc<T>> {
+        let value = self.extensions().borrow_mut().remove(&TypeId::of::<T>())?;
+        value.downcast().ok()
+    }
+
+    fn extensions(&self) -> Rc<Extensions> {
+        if let Some(extensions) = self.get_user_data(&EXTENSIONS) {
+            return extensions;
+        }
+        let extensions = Rc::new(Extensions::default());
+        self.set_user_data(&EXTENSIONS, extensions.clone());
+        extensions
+    }
 }
 
+type Extensions = RefCell<HashMap<TypeId, Rc<dyn Any>>>;
+
+static EXTENSIONS: UserDataKey<Extensions> = UserDataKey::new();
+
 impl fmt::Display for Context {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Context")
     }
 }
 
+// Removes the range from `start` to `end` from sorted, disjoint spans.
+fn cut_spans(spans: &[(f64, f64)], start: f64, end: f64) -> Vec<(f64, f64)> {
+    let mut result = Vec::with_capacity(spans.len() + 1);
+    for &(a, b) in spans {
+        if end <= a || start >= b {
+            result.push((a, b));
+            continue;
+        }
+        if a < start {
+            result.push((a, start));
+        }
+        if end < b {
+            result.push((end, b));
+        }
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -825,4 +1085,116 @@ mod tests {
         );
         assert_eq!(rect.to_string(), "RectangleList");
     }
+
+    #[test]
+    fn text_on_path_follows_path() {
+        let ctx = create_ctx();
+        ctx.set_font_size(4.0);
+        ctx.move_to(0.0, 5.0);
+        ctx.line_to(100.0, 5.0);
+        let path = ctx.copy_path();
+
+        ctx.new_path();
+        ctx.text_path_on_path(&path, "ab", 10.0, TextAlignment::Left);
+        let (x1, y1, _, y2) = ctx.path_extents();
+        assert!(x1 >= 10.0 && y1 < 5.0 && y2 <= 6.0);
+
+        // Text going down a vertical line is rotated a quarter turn.
+        ctx.new_path();
+        ctx.move_to(5.0, 0.0);
+        ctx.line_to(5.0, 100.0);
+        let path = ctx.copy_path();
+        ctx.new_path();
+        ctx.text_path_on_path(&path, "ab", 0.0, TextAlignment::Left);
+        let (x1, y1, x2, _) = ctx.path_extents();
+        assert!(x1 >= 4.0 && x2 > 5.0 && y1 >= 0.0);
+
+        // Glyphs past the end of the path are skipped.
+        ctx.new_path();
+        ctx.text_path_on_path(&path, "ab", 150.0, TextAlignment::Left);
+        assert!(ctx.copy_path().iter().next().is_none());
+    }
+
+    #[test]
+    fn cut() {
+        assert_eq!(
+            cut_spans(&[(0.0, 10.0)], 2.0, 4.0),
+            [(0.0, 2.0), (4.0, 10.0)]
+        );
+        assert_eq!(
+            cut_spans(&[(0.0, 2.0), (4.0, 10.0)], -1.0, 5.0),
+            [(5.0, 10.0)]
+        );
+        assert_eq!(cut_spans(&[(0.0, 2.0)], 3.0, 5.0), [(0.0, 2.0)]);
+    }
+
+    fn underline_ink(text: &str, skip_descenders: bool) -> u32 {
+        let surface = ImageSurface::create(Format::A8, 60, 30).unwrap();
+        {
+            let ctx = Context::new(&surface);
+            ctx.set_font_size(20.0);
+            let (glyphs, _) = ctx.get_scaled_font().text_to_glyphs(2.0, 20.0, text);
+            ctx.move_to(1.0, 1.0);
+            ctx.show_text_decoration(&glyphs, TextDecoration::Underline, skip_descenders);
+            assert_eq!(ctx.get_current_point(), (1.0, 1.0));
+        }
+        let stride = surface.get_stride() as usize;
+        let mut ink = 0;
+        surface
+            .with_data(|data| {
+                for y in 20..30 {
+                    for x in 0..60 {
+                        ink += u32::from(data[y * stride + x]);
+                    }
+                }
+            })
+            .unwrap();
+        ink
+    }
+
+    #[test]
+    fn underline_skips_descenders() {
+        assert!(underline_ink("ag", false) > 0);
+        assert!(underline_ink("ag", true) < underline_ink("ag", false));
+        assert_eq!(underline_ink("ac", true), underline_ink("ac", false));
+    }
+
+    #[test]
+    fn show_text_on_path_keeps_current_path() {
+        let ctx = create_ctx();
+        ctx.move_to(0.0, 5.0);
+        ctx.line_to(10.0, 5.0);
+        let path = ctx.copy_path();
+        ctx.show_text_on_path(&path, "a", 0.0, TextAlignment::Center);
+        assert_eq!(ctx.get_current_point(), (10.0, 5.0));
+    }
+
+    #[test]
+    fn user_data_is_shared_by_clones() {
+        static KEY: UserDataKey<i32> = UserDataKey::new();
+        let ctx = create_ctx();
+        ctx.set_user_data(&KEY, Rc::new(7));
+        let other = ctx.clone();
+        assert_eq!(other.get_user_data(&KEY).as_ref().map(|v| **v), Some(7));
+        other.remove_user_data(&KEY);
+        assert!(ctx.get_user_data(&KEY).is_none());
+    }
+
+    #[test]
+    fn extensions_by_type() {
+        #[derive(Debug, PartialEq)]
+        struct Dpi(f64);
+
+        let ctx = create_ctx();
+        assert!(ctx.get_extension::<Dpi>().is_none());
+        ctx.set_extension(Dpi(96.0));
+        ctx.set_extension(String::from("page 1"));
+        ctx.clone().set_extension(Dpi(144.0));
+
+        assert_eq!(*ctx.get_extension::<Dpi>().unwrap(), Dpi(144.0));
+        assert_eq!(*ctx.get_extension::<String>().unwrap(), "page 1");
+        assert_eq!(*ctx.remove_extension::<Dpi>().unwrap(), Dpi(144.0));
+        assert!(ctx.get_extension::<Dpi>().is_none());
+        assert!(ctx.get_extension::<String>().is_some());
+    }
 }
@@ -47,6 +47,11 @@ macro_rules! gvalue_impl {
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum Antialias {
     Default,
 
@@ -60,6 +65,7 @@ pub enum Antialias {
     Good,
     Best,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -119,10 +125,16 @@ gvalue_impl!(Antialias, ffi::gobject::cairo_gobject_antialias_get_type);
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum FillRule {
     Winding,
     EvenOdd,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -167,11 +179,17 @@ gvalue_impl!(FillRule, ffi::gobject::cairo_gobject_fill_rule_get_type);
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum LineCap {
     Butt,
     Round,
     Square,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -219,11 +237,17 @@ gvalue_impl!(LineCap, ffi::gobject::cairo_gobject_line_cap_get_type);
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum LineJoin {
     Miter,
     Round,
     Bevel,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -271,6 +295,11 @@ gvalue_impl!(LineJoin, ffi::gobject::cairo_gobject_line_join_get_type);
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum Operator {
     Clear,
 
@@ -306,6 +335,7 @@ pub enum Operator {
     HslColor,
     HslLuminosity,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -431,12 +461,18 @@ gvalue_impl!(Operator, ffi::gobject::cairo_gobject_operator_get_type);
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum PathDataType {
     MoveTo,
     LineTo,
     CurveTo,
     ClosePath,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -490,11 +526,17 @@ gvalue_impl!(
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum Content {
     Color,
     Alpha,
     ColorAlpha,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -542,12 +584,18 @@ gvalue_impl!(Content, ffi::gobject::cairo_gobject_content_get_type);
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum Extend {
     None,
     Repeat,
     Reflect,
     Pad,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -598,6 +646,11 @@ gvalue_impl!(Extend, ffi::gobject::cairo_gobject_extend_get_type);
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum Filter {
     Fast,
     Good,
@@ -606,6 +659,7 @@ pub enum Filter {
     Bilinear,
     Gaussian,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -662,6 +716,11 @@ gvalue_impl!(Filter, ffi::gobject::cairo_gobject_filter_get_type);
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum PatternType {
     Solid,
     Surface,
@@ -670,6 +729,7 @@ pub enum PatternType {
     Mesh,
     RasterSource,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -729,11 +789,17 @@ gvalue_impl!(
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum FontSlant {
     Normal,
     Italic,
     Oblique,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -781,10 +847,16 @@ gvalue_impl!(FontSlant, ffi::gobject::cairo_gobject_font_slant_get_type);
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum FontWeight {
     Normal,
     Bold,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -829,10 +901,16 @@ gvalue_impl!(FontWeight, ffi::gobject::cairo_gobject_font_weight_get_type);
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum TextClusterFlags {
     None,
     Backward,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -880,6 +958,11 @@ gvalue_impl!(
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum FontType {
     FontTypeToy,
     FontTypeFt,
@@ -887,6 +970,7 @@ pub enum FontType {
     FontTypeQuartz,
     FontTypeUser,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -940,6 +1024,11 @@ gvalue_impl!(FontType, ffi::gobject::cairo_gobject_font_type_get_type);
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum SubpixelOrder {
     Default,
     Rgb,
@@ -947,6 +1036,7 @@ pub enum SubpixelOrder {
     Vrgb,
     Vbgr,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -1003,6 +1093,11 @@ gvalue_impl!(
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum HintStyle {
     Default,
     None,
@@ -1010,6 +1105,7 @@ pub enum HintStyle {
     Medium,
     Full,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -1063,11 +1159,17 @@ gvalue_impl!(HintStyle, ffi::gobject::cairo_gobject_hint_style_get_type);
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum HintMetrics {
     Default,
     Off,
     On,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -1118,6 +1220,11 @@ gvalue_impl!(
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum SurfaceType {
     Image,
     Pdf,
@@ -1145,6 +1252,7 @@ pub enum SurfaceType {
     Subsurface,
     Cogl,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -1262,6 +1370,11 @@ gvalue_impl!(
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[cfg(any(all(feature = "svg", feature = "v1_16"), feature = "dox"))]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum SvgUnit {
     User,
     Em,
@@ -1274,6 +1387,7 @@ pub enum SvgUnit {
     Pc,
     Percent,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -1342,6 +1456,11 @@ impl fmt::Display for SvgUnit {
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum Format {
     Invalid,
     ARgb32,
@@ -1351,6 +1470,7 @@ pub enum Format {
     Rgb16_565,
     Rgb30,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -1424,11 +1544,17 @@ impl Format {
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum RegionOverlap {
     In,
     Out,
     Part,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -1488,6 +1614,11 @@ bitflags! {
 #[cfg(any(feature = "pdf", feature = "dox"))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum PdfMetadata {
     Title,
     Author,
@@ -1497,6 +1628,7 @@ pub enum PdfMetadata {
     CreateDate,
     ModDate,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -1557,10 +1689,16 @@ impl fmt::Display for PdfMetadata {
 #[cfg(any(feature = "pdf", feature = "dox"))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum PdfVersion {
     _1_4,
     _1_5,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -1606,10 +1744,16 @@ impl fmt::Display for PdfVersion {
 #[cfg(any(feature = "svg", feature = "dox"))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum SvgVersion {
     _1_1,
     _1_2,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -1655,10 +1799,16 @@ impl fmt::Display for SvgVersion {
 #[cfg(any(feature = "ps", feature = "dox"))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum PsLevel {
     _2,
     _3,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -1703,6 +1853,11 @@ impl fmt::Display for PsLevel {
 
 #[derive(Clone, PartialEq, PartialOrd, Copy, Debug)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum MeshCorner {
     MeshCorner0,
     MeshCorner1,
@@ -1757,6 +1912,11 @@ impl fmt::Display for MeshCorner {
 #[cfg(any(feature = "freetype", feature = "dox"))]
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum FtSynthesize {
     Bold,
     Oblique,
@@ -1806,10 +1966,16 @@ impl fmt::Display for FtSynthesize {
 #[cfg(any(feature = "script", feature = "dox"))]
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum ScriptMode {
     Ascii,
     Binary,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -1854,6 +2020,11 @@ impl fmt::Display for ScriptMode {
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Copy)]
 #[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum DeviceType {
     Ascii,
     Binary,
@@ -1865,6 +2036,7 @@ pub enum DeviceType {
     Win32,
     Invalid,
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     __Unknown(i32),
 }
 
@@ -1947,4 +2119,19 @@ mod tests {
     fn stride_works() {
         assert!(Format::Rgb24.stride_for_width(1).unwrap() == 4);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_rejects_unknown_values() {
+        assert_eq!(
+            ::serde_json::to_string(&Operator::Over).unwrap(),
+            r#""Over""#
+        );
+        assert_eq!(
+            ::serde_json::from_str::<Operator>(r#""Over""#).unwrap(),
+            Operator::Over
+        );
+        assert!(::serde_json::to_string(&Operator::__Unknown(1234)).is_err());
+        assert!(::serde_json::from_str::<Operator>(r#"{"__Unknown":1234}"#).is_err());
+    }
 }
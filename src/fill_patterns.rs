@@ -0,0 +1,245 @@
+// Copyright 2020, The Gtk-rs Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <https://opensource.org/licenses/MIT>
+
+use std::f64::consts::{PI, SQRT_2};
+
+use context::Context;
+use enums::{Content, Extend};
+use error::Error;
+use matrices::Matrix;
+use patterns::SurfacePattern;
+use recording_surface::RecordingSurface;
+use rectangle::Rectangle;
+use Rgba;
+
+/// Options of the fill patterns made by `SurfacePattern::hatch`, `cross_hatch`, `grid`, `dots`
+/// and `checkerboard`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FillPatternOptions {
+    /// Distance between lines or dots, or size of squares, in user space.
+    pub spacing: f64,
+    /// Rotation of the pattern in radians.
+    pub angle: f64,
+    /// Width of lines, or diameter of dots, in user space.
+    pub line_width: f64,
+    pub color: Rgba,
+    /// Color behind the lines and dots, or of the other squares of checkerboards.
+    pub background: Rgba,
+    /// Device pixels per user space unit where the pattern is used. It must match the device
+    /// scale of the target surface (`Surface::get_device_scale`) times the scale of the
+    /// current matrix of the context the pattern is used with.
+    ///
+    /// The spacing is rounded for tiles to span a whole number of device pixels at this
+    /// scale, which keeps seams from showing between them. With another scale the pattern
+    /// keeps its size in user space, but tiles are resampled and lines get blurry edges.
+    pub scale: f64,
+}
+
+impl FillPatternOptions {
+    /// Creates options for lines 1 unit wide of `color` on a transparent background.
+    pub fn new(spacing: f64, color: Rgba) -> FillPatternOptions {
+        FillPatternOptions {
+            spacing,
+            angle: 0.0,
+            line_width: 1.0,
+            color,
+            background: Rgba::new(0.0, 0.0, 0.0, 0.0),
+            scale: 1.0,
+        }
+    }
+}
+
+// Each pattern repeats a square tile drawn into a recording surface, so that it stays vector
+// data in PDF, PostScript and SVG output.
+impl SurfacePattern {
+    /// Creates a pattern of parallel diagonal lines, going up to the right before rotation.
+    pub fn hatch(options: &FillPatternOptions) -> Result<SurfacePattern, Error> {
+        tile_pattern(options, options.spacing * SQRT_2, |cr, size| {
+            for &dx in &[-size, 0.0, size] {
+                cr.move_to(dx, size);
+                cr.line_to(dx + size, 0.0);
+            }
+            cr.stroke();
+        })
+    }
+
+    /// Creates a pattern of lines in both diagonal directions.
+    pub fn cross_hatch(options: &FillPatternOptions) -> Result<SurfacePattern, Error> {
+        tile_pattern(options, options.spacing * SQRT_2, |cr, size| {
+            for &dx in &[-size, 0.0, size] {
+                cr.move_to(dx, size);
+                cr.line_to(dx + size, 0.0);
+                cr.move_to(dx, 0.0);
+                cr.line_to(dx + size, size);
+            }
+            cr.stroke();
+        })
+    }
+
+    /// Creates a pattern of horizontal and vertical lines before rotation.
+    pub fn grid(options: &FillPatternOptions) -> Result<SurfacePattern, Error> {
+        tile_pattern(options, options.spacing, |cr, size| {
+            // Lines go through the middle of the tile and past its sides.
+            let line_width = cr.get_line_width();
+            cr.move_to(-line_width, size / 2.0);
+            cr.line_to(size + line_width, size / 2.0);
+            cr.move_to(size / 2.0, -line_width);
+            cr.line_to(size / 2.0, size + line_width);
+            cr.stroke();
+        })
+    }
+
+    /// Creates a pattern of dots with a diameter of `line_width`, on a square grid.
+    pub fn dots(options: &FillPatternOptions) -> Result<SurfacePattern, Error> {
+        tile_pattern(options, options.spacing, |cr, size| {
+            let radius = cr.get_line_width() / 2.0;
+            cr.arc(size / 2.0, size / 2.0, radius, 0.0, 2.0 * PI);
+            cr.fill();
+        })
+    }
+
+    /// Creates a checkerboard of `spacing` sized squares of the color and background color.
+    pub fn checkerboard(options: &FillPatternOptions) -> Result<SurfacePattern, Error> {
+        // Tiles hold two squares of each color, so their size in pixels must be even.
+        let options = FillPatternOptions {
+            spacing: (options.spacing * options.scale).round().max(1.0) / options.scale,
+            ..*options
+        };
+        tile_pattern(&options, options.spacing * 2.0, |cr, size| {
+            let half = size / 2.0;
+            cr.rectangle(0.0, 0.0, half, half);
+            cr.rectangle(half, half, half, half);
+            cr.fill();
+        })
+    }
+}
+
+// Creates a repeating pattern of a square tile about `size` user space units wide. `draw`
+// gets the context of the tile, in user space units, with the line width and color set, and
+// the actual size of the tile.
+fn tile_pattern<F: FnOnce(&Context, f64)>(
+    options: &FillPatternOptions,
+    size: f64,
+    draw: F,
+) -> Result<SurfacePattern, Error> {
+    if !(size > 0.0 && size.is_finite() && options.scale > 0.0 && options.scale.is_finite()) {
+        return Err(Error::InvalidSize);
+    }
+    let pixels = (size * options.scale).round().max(1.0);
+    let tile = RecordingSurface::create(
+        Content::ColorAlpha,
+        Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: pixels,
+            height: pixels,
+        },
+    )?;
+
+    {
+        let cr = Context::new(&tile);
+        cr.scale(options.scale, options.scale);
+        if options.background.alpha > 0.0 {
+            cr.set_source_color(options.background);
+            cr.paint();
+        }
+        cr.set_source_color(options.color);
+        cr.set_line_width(options.line_width);
+        draw(&cr, pixels / options.scale);
+        cr.status()?;
+    }
+
+    let pattern = SurfacePattern::create(&tile);
+    pattern.set_extend(Extend::Repeat);
+    let mut matrix = Matrix::identity();
+    matrix.scale(options.scale, options.scale);
+    matrix.rotate(-options.angle);
+    pattern.set_matrix(matrix);
+    Ok(pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enums::Format;
+    use image_surface::ImageSurface;
+    use surface::SurfaceKind;
+
+    fn alpha_at(pattern: &SurfacePattern, scale: f64, x: usize, y: usize) -> u8 {
+        let size = (40.0 * scale) as i32;
+        let mut surface = ImageSurface::create(Format::A8, size, size).unwrap();
+        {
+            let cr = Context::new(&surface);
+            cr.scale(scale, scale);
+            cr.set_source(pattern);
+            cr.paint();
+        }
+        surface.flush();
+        let stride = surface.get_stride() as usize;
+        let data = surface.get_data().unwrap();
+        data[y * stride + x]
+    }
+
+    #[test]
+    fn tiles_are_vector() {
+        let options = FillPatternOptions::new(10.0, Rgba::rgb(0.0, 0.0, 0.0));
+        let pattern = SurfacePattern::hatch(&options).unwrap();
+        assert_eq!(pattern.get_extend(), Extend::Repeat);
+        match pattern.get_surface().kind() {
+            SurfaceKind::Recording(tile) => {
+                let extents = tile.get_extents().unwrap();
+                assert_eq!((extents.width, extents.height), (14.0, 14.0));
+            }
+            kind => panic!("unexpected {:?}", kind),
+        }
+
+        let options = FillPatternOptions {
+            spacing: 0.0,
+            ..options
+        };
+        assert_eq!(
+            SurfacePattern::dots(&options).unwrap_err(),
+            Error::InvalidSize
+        );
+    }
+
+    #[test]
+    fn grid_lines() {
+        let mut options = FillPatternOptions::new(10.0, Rgba::rgb(0.0, 0.0, 0.0));
+        options.line_width = 2.0;
+        let grid = SurfacePattern::grid(&options).unwrap();
+        // Lines are in the middle of the cells, 2 pixels wide.
+        assert_eq!(alpha_at(&grid, 1.0, 4, 1), 255);
+        assert_eq!(alpha_at(&grid, 1.0, 25, 33), 255);
+        assert_eq!(alpha_at(&grid, 1.0, 1, 1), 0);
+        assert_eq!(alpha_at(&grid, 1.0, 31, 21), 0);
+
+        // At a device scale of 1.5, cells are 15 pixels wide.
+        options.scale = 1.5;
+        let grid = SurfacePattern::grid(&options).unwrap();
+        assert_eq!(alpha_at(&grid, 1.5, 7, 1), 255);
+        assert_eq!(alpha_at(&grid, 1.5, 22, 1), 255);
+        assert_eq!(alpha_at(&grid, 1.5, 1, 1), 0);
+        assert_eq!(alpha_at(&grid, 1.5, 15, 1), 0);
+
+        // With a scale that does not match the target, cells keep their size in user space.
+        options.scale = 1.0;
+        let grid = SurfacePattern::grid(&options).unwrap();
+        assert!(alpha_at(&grid, 1.5, 7, 1) > 192);
+        assert!(alpha_at(&grid, 1.5, 22, 1) > 192);
+        assert!(alpha_at(&grid, 1.5, 1, 1) < 32);
+        assert!(alpha_at(&grid, 1.5, 15, 1) < 32);
+    }
+
+    #[test]
+    fn checkerboard_squares() {
+        let mut options = FillPatternOptions::new(5.0, Rgba::rgb(0.0, 0.0, 0.0));
+        options.background = Rgba::new(0.0, 0.0, 0.0, 0.5);
+        let checkerboard = SurfacePattern::checkerboard(&options).unwrap();
+        assert_eq!(alpha_at(&checkerboard, 1.0, 2, 2), 255);
+        assert!((127..=128).contains(&alpha_at(&checkerboard, 1.0, 7, 2)));
+        assert_eq!(alpha_at(&checkerboard, 1.0, 12, 2), 255);
+        assert_eq!(alpha_at(&checkerboard, 1.0, 7, 7), 255);
+    }
+}
@@ -0,0 +1,354 @@
+// Copyright 2020, The Gtk-rs Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <https://opensource.org/licenses/MIT>
+
+use std::ops::Range;
+
+use unicode_bidi::{BidiInfo, Level};
+
+use enums::TextClusterFlags;
+use ffi::{Glyph, TextCluster};
+
+use super::ScaledFont;
+
+/// Base direction of a paragraph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextDirection {
+    Ltr,
+    Rtl,
+}
+
+/// A run of text with a single direction, laid out in visual order.
+///
+/// `clusters` map `glyphs` to the run's text, `&text[range]`, as expected by
+/// `Context::show_text_glyphs`.
+#[derive(Clone, Debug)]
+pub struct BidiRun {
+    /// Byte range of the run in the logical text.
+    pub range: Range<usize>,
+    pub glyphs: Vec<Glyph>,
+    pub clusters: Vec<TextCluster>,
+    /// `TextClusterFlags::Backward` for right-to-left runs.
+    pub cluster_flags: TextClusterFlags,
+    pub x_advance: f64,
+}
+
+impl ScaledFont {
+    /// Converts `text` to glyphs like `text_to_glyphs`, after reordering it visually with the
+    /// Unicode Bidirectional Algorithm.
+    ///
+    /// Runs are returned from left to right, starting at (`x`, `y`). The base direction is
+    /// taken from the first strong character of each paragraph if `direction` is `None`.
+    pub fn text_to_glyphs_bidi(
+        &self,
+        x: f64,
+        y: f64,
+        text: &str,
+        direction: Option<TextDirection>,
+    ) -> Vec<BidiRun> {
+        let level = direction.map(|direction| match direction {
+            TextDirection::Ltr => Level::ltr(),
+            TextDirection::Rtl => Level::rtl(),
+        });
+        let info = BidiInfo::new(text, level);
+
+        let mut pen_x = x;
+        let mut runs = Vec::new();
+        for paragraph in &info.paragraphs {
+            let (levels, level_runs) = info.visual_runs(paragraph, paragraph.range.clone());
+            for range in level_runs {
+                if range.start == range.end {
+                    continue;
+                }
+                let run = if levels[range.start].is_rtl() {
+                    self.rtl_run(pen_x, y, text, range)
+                } else {
+                    let (glyphs, clusters) = self.text_to_glyphs(pen_x, y, &text[range.clone()]);
+                    BidiRun {
+                        x_advance: self.text_extents(&text[range.clone()]).x_advance,
+                        range,
+                        glyphs,
+                        clusters,
+                        cluster_flags: TextClusterFlags::None,
+                    }
+                };
+                pen_x += run.x_advance;
+                runs.push(run);
+            }
+        }
+
+        runs
+    }
+
+    // Lays out a right-to-left run: clusters are placed from right to left, each keeping its
+    // own glyph order, and listed backward.
+    fn rtl_run(&self, x: f64, y: f64, text: &str, range: Range<usize>) -> BidiRun {
+        let mirrored: String = text[range.clone()].chars().map(mirror).collect();
+        let (glyphs, clusters) = self.text_to_glyphs(0.0, 0.0, &mirrored);
+        let x_advance = self.text_extents(&mirrored).x_advance;
+
+        let mut starts = Vec::with_capacity(clusters.len());
+        let mut glyph = 0;
+        for cluster in &clusters {
+            let start = glyphs.get(glyph).map_or(x_advance, |g| g.x);
+            starts.push((glyph, start));
+            glyph += cluster.num_glyphs as usize;
+        }
+
+        let mut visual_glyphs = Vec::with_capacity(glyphs.len());
+        for (i, cluster) in clusters.iter().enumerate().rev() {
+            let (first, start) = starts[i];
+            let end = starts.get(i + 1).map_or(x_advance, |&(_, end)| end);
+            for g in &glyphs[first..first + cluster.num_glyphs as usize] {
+                visual_glyphs.push(Glyph {
+                    index: g.index,
+                    x: x + x_advance - end + (g.x - start),
+                    y: y + g.y,
+                });
+            }
+        }
+
+        BidiRun {
+            range,
+            glyphs: visual_glyphs,
+            clusters: clusters.into_iter().rev().collect(),
+            cluster_flags: TextClusterFlags::Backward,
+            x_advance,
+        }
+    }
+}
+
+// Mirrors paired punctuation in right-to-left runs. Pairs have the same UTF-8 length, so
+// cluster byte counts stay valid for the original text.
+fn mirror(c: char) -> char {
+    MIRRORED_PAIRS
+        .binary_search_by_key(&c, |&(from, _)| from)
+        .map(|index| MIRRORED_PAIRS[index].1)
+        .unwrap_or(c)
+}
+
+// The Bidi_Paired_Bracket pairs of Unicode 16's BidiBrackets.txt, plus `<>`, guillemets and
+// the common set and order relations. Other Bidi_Mirrored characters are left as is. Sorted.
+const MIRRORED_PAIRS: &[(char, char)] = &[
+    ('(', ')'),
+    (')', '('),
+    ('<', '>'),
+    ('>', '<'),
+    ('[', ']'),
+    (']', '['),
+    ('{', '}'),
+    ('}', '{'),
+    ('\u{ab}', '\u{bb}'),
+    ('\u{bb}', '\u{ab}'),
+    ('\u{f3a}', '\u{f3b}'),
+    ('\u{f3b}', '\u{f3a}'),
+    ('\u{f3c}', '\u{f3d}'),
+    ('\u{f3d}', '\u{f3c}'),
+    ('\u{169b}', '\u{169c}'),
+    ('\u{169c}', '\u{169b}'),
+    ('\u{2039}', '\u{203a}'),
+    ('\u{203a}', '\u{2039}'),
+    ('\u{2045}', '\u{2046}'),
+    ('\u{2046}', '\u{2045}'),
+    ('\u{207d}', '\u{207e}'),
+    ('\u{207e}', '\u{207d}'),
+    ('\u{208d}', '\u{208e}'),
+    ('\u{208e}', '\u{208d}'),
+    ('\u{2208}', '\u{220b}'),
+    ('\u{220b}', '\u{2208}'),
+    ('\u{2264}', '\u{2265}'),
+    ('\u{2265}', '\u{2264}'),
+    ('\u{226a}', '\u{226b}'),
+    ('\u{226b}', '\u{226a}'),
+    ('\u{227a}', '\u{227b}'),
+    ('\u{227b}', '\u{227a}'),
+    ('\u{2282}', '\u{2283}'),
+    ('\u{2283}', '\u{2282}'),
+    ('\u{2286}', '\u{2287}'),
+    ('\u{2287}', '\u{2286}'),
+    ('\u{2308}', '\u{2309}'),
+    ('\u{2309}', '\u{2308}'),
+    ('\u{230a}', '\u{230b}'),
+    ('\u{230b}', '\u{230a}'),
+    ('\u{2329}', '\u{232a}'),
+    ('\u{232a}', '\u{2329}'),
+    ('\u{2768}', '\u{2769}'),
+    ('\u{2769}', '\u{2768}'),
+    ('\u{276a}', '\u{276b}'),
+    ('\u{276b}', '\u{276a}'),
+    ('\u{276c}', '\u{276d}'),
+    ('\u{276d}', '\u{276c}'),
+    ('\u{276e}', '\u{276f}'),
+    ('\u{276f}', '\u{276e}'),
+    ('\u{2770}', '\u{2771}'),
+    ('\u{2771}', '\u{2770}'),
+    ('\u{2772}', '\u{2773}'),
+    ('\u{2773}', '\u{2772}'),
+    ('\u{2774}', '\u{2775}'),
+    ('\u{2775}', '\u{2774}'),
+    ('\u{27c5}', '\u{27c6}'),
+    ('\u{27c6}', '\u{27c5}'),
+    ('\u{27e6}', '\u{27e7}'),
+    ('\u{27e7}', '\u{27e6}'),
+    ('\u{27e8}', '\u{27e9}'),
+    ('\u{27e9}', '\u{27e8}'),
+    ('\u{27ea}', '\u{27eb}'),
+    ('\u{27eb}', '\u{27ea}'),
+    ('\u{27ec}', '\u{27ed}'),
+    ('\u{27ed}', '\u{27ec}'),
+    ('\u{27ee}', '\u{27ef}'),
+    ('\u{27ef}', '\u{27ee}'),
+    ('\u{2983}', '\u{2984}'),
+    ('\u{2984}', '\u{2983}'),
+    ('\u{2985}', '\u{2986}'),
+    ('\u{2986}', '\u{2985}'),
+    ('\u{2987}', '\u{2988}'),
+    ('\u{2988}', '\u{2987}'),
+    ('\u{2989}', '\u{298a}'),
+    ('\u{298a}', '\u{2989}'),
+    ('\u{298b}', '\u{298c}'),
+    ('\u{298c}', '\u{298b}'),
+    ('\u{298d}', '\u{2990}'),
+    ('\u{298e}', '\u{298f}'),
+    ('\u{298f}', '\u{298e}'),
+    ('\u{2990}', '\u{298d}'),
+    ('\u{2991}', '\u{2992}'),
+    ('\u{2992}', '\u{2991}'),
+    ('\u{2993}', '\u{2994}'),
+    ('\u{2994}', '\u{2993}'),
+    ('\u{2995}', '\u{2996}'),
+    ('\u{2996}', '\u{2995}'),
+    ('\u{2997}', '\u{2998}'),
+    ('\u{2998}', '\u{2997}'),
+    ('\u{29d8}', '\u{29d9}'),
+    ('\u{29d9}', '\u{29d8}'),
+    ('\u{29da}', '\u{29db}'),
+    ('\u{29db}', '\u{29da}'),
+    ('\u{29fc}', '\u{29fd}'),
+    ('\u{29fd}', '\u{29fc}'),
+    ('\u{2e22}', '\u{2e23}'),
+    ('\u{2e23}', '\u{2e22}'),
+    ('\u{2e24}', '\u{2e25}'),
+    ('\u{2e25}', '\u{2e24}'),
+    ('\u{2e26}', '\u{2e27}'),
+    ('\u{2e27}', '\u{2e26}'),
+    ('\u{2e28}', '\u{2e29}'),
+    ('\u{2e29}', '\u{2e28}'),
+    ('\u{2e55}', '\u{2e56}'),
+    ('\u{2e56}', '\u{2e55}'),
+    ('\u{2e57}', '\u{2e58}'),
+    ('\u{2e58}', '\u{2e57}'),
+    ('\u{2e59}', '\u{2e5a}'),
+    ('\u{2e5a}', '\u{2e59}'),
+    ('\u{2e5b}', '\u{2e5c}'),
+    ('\u{2e5c}', '\u{2e5b}'),
+    ('\u{3008}', '\u{3009}'),
+    ('\u{3009}', '\u{3008}'),
+    ('\u{300a}', '\u{300b}'),
+    ('\u{300b}', '\u{300a}'),
+    ('\u{300c}', '\u{300d}'),
+    ('\u{300d}', '\u{300c}'),
+    ('\u{300e}', '\u{300f}'),
+    ('\u{300f}', '\u{300e}'),
+    ('\u{3010}', '\u{3011}'),
+    ('\u{3011}', '\u{3010}'),
+    ('\u{3014}', '\u{3015}'),
+    ('\u{3015}', '\u{3014}'),
+    ('\u{3016}', '\u{3017}'),
+    ('\u{3017}', '\u{3016}'),
+    ('\u{3018}', '\u{3019}'),
+    ('\u{3019}', '\u{3018}'),
+    ('\u{301a}', '\u{301b}'),
+    ('\u{301b}', '\u{301a}'),
+    ('\u{fe59}', '\u{fe5a}'),
+    ('\u{fe5a}', '\u{fe59}'),
+    ('\u{fe5b}', '\u{fe5c}'),
+    ('\u{fe5c}', '\u{fe5b}'),
+    ('\u{fe5d}', '\u{fe5e}'),
+    ('\u{fe5e}', '\u{fe5d}'),
+    ('\u{ff08}', '\u{ff09}'),
+    ('\u{ff09}', '\u{ff08}'),
+    ('\u{ff3b}', '\u{ff3d}'),
+    ('\u{ff3d}', '\u{ff3b}'),
+    ('\u{ff5b}', '\u{ff5d}'),
+    ('\u{ff5d}', '\u{ff5b}'),
+    ('\u{ff5f}', '\u{ff60}'),
+    ('\u{ff60}', '\u{ff5f}'),
+    ('\u{ff62}', '\u{ff63}'),
+    ('\u{ff63}', '\u{ff62}'),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enums::{FontSlant, FontWeight};
+    use font::{FontFace, FontOptions};
+    use matrices::Matrix;
+
+    fn scaled_font() -> ScaledFont {
+        let face = FontFace::toy_create("sans-serif", FontSlant::Normal, FontWeight::Normal);
+        let mut font_matrix = Matrix::identity();
+        font_matrix.scale(10.0, 10.0);
+        ScaledFont::new(
+            &face,
+            &font_matrix,
+            &Matrix::identity(),
+            &FontOptions::new(),
+        )
+    }
+
+    fn total_bytes(run: &BidiRun) -> usize {
+        run.clusters.iter().map(|c| c.num_bytes as usize).sum()
+    }
+
+    #[test]
+    fn ltr_only() {
+        let font = scaled_font();
+        let runs = font.text_to_glyphs_bidi(0.0, 0.0, "Invoice", None);
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].range, 0..7);
+        assert_eq!(runs[0].cluster_flags, TextClusterFlags::None);
+    }
+
+    #[test]
+    fn mixed_runs_in_visual_order() {
+        let font = scaled_font();
+        let hebrew = "\u{5e9}\u{5dc}\u{5d5}\u{5dd}";
+        let text = format!("Name: {}", hebrew);
+        let runs = font.text_to_glyphs_bidi(5.0, 0.0, &text, Some(TextDirection::Ltr));
+        assert_eq!(runs.len(), 2);
+        assert_eq!(&text[runs[0].range.clone()], "Name: ");
+        assert_eq!(&text[runs[1].range.clone()], hebrew);
+
+        let rtl = &runs[1];
+        assert_eq!(rtl.cluster_flags, TextClusterFlags::Backward);
+        assert_eq!(total_bytes(rtl), hebrew.len());
+        assert!((rtl.glyphs[0].x - (5.0 + runs[0].x_advance)).abs() < 1e-9);
+        assert!(rtl.glyphs.windows(2).all(|w| w[1].x > w[0].x));
+
+        // The first glyph on the left is the last letter of the word.
+        let (last, _) = font.text_to_glyphs(0.0, 0.0, "\u{5dd}");
+        assert_eq!(rtl.glyphs[0].index, last[0].index);
+    }
+
+    #[test]
+    fn rtl_base_direction() {
+        let font = scaled_font();
+        let text = "\u{5e9}\u{5dc}\u{5d5}\u{5dd} 42";
+        let runs = font.text_to_glyphs_bidi(0.0, 0.0, text, Some(TextDirection::Rtl));
+        assert_eq!(&text[runs[0].range.clone()], "42");
+        assert!(runs[1..]
+            .iter()
+            .all(|run| run.cluster_flags == TextClusterFlags::Backward));
+    }
+
+    #[test]
+    fn mirroring() {
+        assert_eq!(mirror('('), ')');
+        assert_eq!(mirror('\u{bb}'), '\u{ab}');
+        assert_eq!(mirror('\u{300c}'), '\u{300d}');
+        assert_eq!(mirror('\u{2264}'), '\u{2265}');
+        assert_eq!(mirror('a'), 'a');
+        assert!(MIRRORED_PAIRS.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+}
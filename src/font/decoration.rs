@@ -0,0 +1,147 @@
+// Copyright 2020, The Gtk-rs Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <https://opensource.org/licenses/MIT>
+
+use super::ScaledFont;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextDecoration {
+    Underline,
+    Strikethrough,
+    Overline,
+}
+
+/// Placement of the lines drawn by `Context::show_text_decoration`.
+///
+/// Values are in font space, where the em square is 1 unit high and y grows downwards, so
+/// positions below the baseline are positive. Positions are those of the middle of the lines.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextDecorationMetrics {
+    pub underline_position: f64,
+    pub underline_thickness: f64,
+    pub strikethrough_position: f64,
+    pub strikethrough_thickness: f64,
+    pub overline_position: f64,
+}
+
+impl TextDecorationMetrics {
+    /// Returns the position and thickness of the line for `decoration`.
+    pub fn get(&self, decoration: TextDecoration) -> (f64, f64) {
+        match decoration {
+            TextDecoration::Underline => (self.underline_position, self.underline_thickness),
+            TextDecoration::Strikethrough => {
+                (self.strikethrough_position, self.strikethrough_thickness)
+            }
+            TextDecoration::Overline => (self.overline_position, self.underline_thickness),
+        }
+    }
+}
+
+impl ScaledFont {
+    /// Returns the underline and strikeout metrics of the font.
+    ///
+    /// They are read from the FreeType face when possible, from the `post` and `OS/2` tables
+    /// of scalable fonts. Otherwise, they are estimated from the font's `FontExtents`.
+    pub fn get_decoration_metrics(&self) -> TextDecorationMetrics {
+        let font_matrix = self.get_font_matrix();
+        let em = font_matrix.xy.hypot(font_matrix.yy);
+        let extents = self.extents();
+        let (ascent, descent) = if em > 0.0 {
+            (extents.ascent / em, extents.descent / em)
+        } else {
+            (extents.ascent, extents.descent)
+        };
+
+        let thickness = descent / 4.0;
+        let estimate = TextDecorationMetrics {
+            underline_position: descent / 2.0,
+            underline_thickness: thickness,
+            strikethrough_position: -ascent * 0.3,
+            strikethrough_thickness: thickness,
+            overline_position: -ascent + thickness / 2.0,
+        };
+
+        #[cfg(any(feature = "freetype", feature = "dox"))]
+        {
+            if let Some(metrics) = self.ft_decoration_metrics(estimate) {
+                return metrics;
+            }
+        }
+
+        estimate
+    }
+
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    fn ft_decoration_metrics(
+        &self,
+        estimate: TextDecorationMetrics,
+    ) -> Option<TextDecorationMetrics> {
+        let face = self.lock_ft_face().ok()?;
+        if face.units_per_EM == 0 || face.underline_thickness <= 0 {
+            return None;
+        }
+
+        let units = f64::from(face.units_per_EM);
+        let underline_thickness = f64::from(face.underline_thickness) / units;
+        let mut metrics = TextDecorationMetrics {
+            underline_position: -f64::from(face.underline_position) / units,
+            underline_thickness,
+            overline_position: -f64::from(face.ascender) / units + underline_thickness / 2.0,
+            ..estimate
+        };
+
+        unsafe {
+            let os2 = freetype_crate::freetype::FT_Get_Sfnt_Table(
+                face.as_ptr(),
+                freetype_crate::freetype::FT_Sfnt_Tag::FT_SFNT_OS2,
+            ) as *const freetype_crate::tt_os2::TT_OS2;
+            if !os2.is_null() && (*os2).version != 0xffff && (*os2).yStrikeoutSize > 0 {
+                let size = f64::from((*os2).yStrikeoutSize) / units;
+                metrics.strikethrough_thickness = size;
+                metrics.strikethrough_position =
+                    -f64::from((*os2).yStrikeoutPosition) / units + size / 2.0;
+            }
+        }
+
+        Some(metrics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enums::{FontSlant, FontWeight};
+    use font::{FontFace, FontOptions};
+    use matrices::Matrix;
+
+    fn metrics(size: f64) -> TextDecorationMetrics {
+        let face = FontFace::toy_create("sans-serif", FontSlant::Normal, FontWeight::Normal);
+        let mut font_matrix = Matrix::identity();
+        font_matrix.scale(size, size);
+        ScaledFont::new(
+            &face,
+            &font_matrix,
+            &Matrix::identity(),
+            &FontOptions::new(),
+        )
+        .get_decoration_metrics()
+    }
+
+    #[test]
+    fn metrics_are_plausible() {
+        let m = metrics(20.0);
+        assert!(m.underline_position > 0.0 && m.underline_position < 0.5);
+        assert!(m.underline_thickness > 0.0 && m.underline_thickness < 0.2);
+        assert!(m.strikethrough_position < 0.0 && m.strikethrough_position > -0.7);
+        assert!(m.strikethrough_thickness > 0.0);
+        assert!(m.overline_position < m.strikethrough_position);
+        assert_eq!(m.get(TextDecoration::Overline).1, m.underline_thickness);
+    }
+
+    #[test]
+    fn metrics_do_not_depend_on_size() {
+        let (small, large) = (metrics(10.0), metrics(40.0));
+        assert!((small.strikethrough_position - large.strikethrough_position).abs() < 0.05);
+        assert!((small.underline_position - large.underline_position).abs() < 0.05);
+    }
+}
@@ -10,6 +10,10 @@ use enums::{FontSlant, FontType, FontWeight};
 
 #[cfg(any(feature = "freetype", feature = "dox"))]
 use enums::FtSynthesize;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+use error::Error;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+use font::{FontOptions, FontconfigQuery};
 
 use utils::status_to_result;
 
@@ -71,6 +75,22 @@ impl FontFace {
         font_face
     }
 
+    /// Creates a FreeType-backed font face for the font that fontconfig considers the best
+    /// match for `query`, after applying the substitutions requested by `options`.
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn from_fontconfig(
+        query: &FontconfigQuery,
+        options: &FontOptions,
+    ) -> Result<FontFace, Error> {
+        let pattern = query.find_match(options)?;
+        let font_face = unsafe {
+            FontFace::from_raw_full(ffi::cairo_ft_font_face_create_for_pattern(pattern.as_ptr()))
+        };
+        let status = unsafe { ffi::cairo_font_face_status(font_face.to_raw_none()) };
+        status_to_result(status)?;
+        Ok(font_face)
+    }
+
     #[cfg(feature = "use_glib")]
     pub unsafe fn from_raw_full(ptr: *mut ffi::cairo_font_face_t) -> FontFace {
         from_glib_full(ptr)
@@ -90,6 +110,7 @@ impl FontFace {
     #[cfg(not(feature = "use_glib"))]
     pub unsafe fn from_raw_none(ptr: *mut ffi::cairo_font_face_t) -> FontFace {
         assert!(!ptr.is_null());
+        ffi::cairo_font_face_reference(ptr);
         FontFace(ptr::NonNull::new_unchecked(ptr))
     }
 
@@ -167,3 +188,30 @@ pub(crate) unsafe fn to_optional_string(str: *const c_char) -> Option<String> {
         Some(String::from_utf8_lossy(CStr::from_ptr(str).to_bytes()).into_owned())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use font::{FontOptions, ScaledFont};
+    use matrices::Matrix;
+
+    #[test]
+    fn clones_hold_a_reference() {
+        let face = FontFace::toy_create("sans-serif", FontSlant::Normal, FontWeight::Normal);
+        let count = face.get_reference_count();
+        let clone = face.clone();
+        assert_eq!(face.get_reference_count(), count + 1);
+        drop(clone);
+        assert_eq!(face.get_reference_count(), count);
+
+        let scaled_font = ScaledFont::new(
+            &face,
+            &Matrix::identity(),
+            &Matrix::identity(),
+            &FontOptions::new(),
+        );
+        let count = face.get_reference_count();
+        drop(scaled_font.get_font_face());
+        assert_eq!(face.get_reference_count(), count);
+    }
+}
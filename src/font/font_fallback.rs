@@ -0,0 +1,378 @@
+// Copyright 2020, The Gtk-rs Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <https://opensource.org/licenses/MIT>
+
+use std::ops::Range;
+
+use context::Context;
+use enums::TextClusterFlags;
+use error::Error;
+use ffi::{FontExtents, Glyph, TextCluster, TextExtents};
+use matrices::Matrix;
+
+use super::{FontFace, FontOptions, ScaledFont};
+
+/// Fonts of the same size, each used for the characters the fonts before it have no glyph for.
+///
+/// Coverage is looked up in the character maps of the FreeType faces behind the fonts.
+/// Characters no font covers are shown with the first one.
+#[derive(Clone, Debug)]
+pub struct FontFallbackChain {
+    fonts: Vec<ScaledFont>,
+}
+
+/// A part of a text shown with a single font of a `FontFallbackChain`.
+#[derive(Clone, Debug)]
+pub struct FallbackRun {
+    /// Byte range of the run in the text.
+    pub range: Range<usize>,
+    /// Index of the run's font in the chain.
+    pub font: usize,
+    pub glyphs: Vec<Glyph>,
+    /// Clusters mapping `glyphs` to `&text[range]`.
+    pub clusters: Vec<TextCluster>,
+    pub x_advance: f64,
+}
+
+impl FontFallbackChain {
+    /// Creates a chain from `faces` in order of preference, all with the same matrices and
+    /// options.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `faces` is empty.
+    pub fn new(
+        faces: &[FontFace],
+        font_matrix: &Matrix,
+        ctm: &Matrix,
+        options: &FontOptions,
+    ) -> FontFallbackChain {
+        FontFallbackChain::from_scaled_fonts(
+            faces
+                .iter()
+                .map(|face| ScaledFont::new(face, font_matrix, ctm, options))
+                .collect(),
+        )
+    }
+
+    /// # Panics
+    ///
+    /// Panics if `fonts` is empty.
+    pub fn from_scaled_fonts(fonts: Vec<ScaledFont>) -> FontFallbackChain {
+        assert!(
+            !fonts.is_empty(),
+            "A font fallback chain needs at least one font"
+        );
+        FontFallbackChain { fonts }
+    }
+
+    pub fn get_scaled_fonts(&self) -> &[ScaledFont] {
+        &self.fonts
+    }
+
+    /// Returns the largest metrics of the fonts, so that lines keep the same height whichever
+    /// fonts their text uses.
+    pub fn extents(&self) -> FontExtents {
+        self.fonts.iter().map(ScaledFont::extents).fold(
+            FontExtents {
+                ascent: 0.0,
+                descent: 0.0,
+                height: 0.0,
+                max_x_advance: 0.0,
+                max_y_advance: 0.0,
+            },
+            |a, b| FontExtents {
+                ascent: a.ascent.max(b.ascent),
+                descent: a.descent.max(b.descent),
+                height: a.height.max(b.height),
+                max_x_advance: a.max_x_advance.max(b.max_x_advance),
+                max_y_advance: a.max_y_advance.max(b.max_y_advance),
+            },
+        )
+    }
+
+    /// Splits `text` into ranges of characters shown with the same font, and returns them with
+    /// the index of their font.
+    ///
+    /// Combining marks, variation selectors and characters joined with a zero width joiner
+    /// stay with the font of the character before them. Whitespace stays with the current font
+    /// if it has a glyph for it.
+    ///
+    /// Returns `Error::FontTypeMismatch` if a font is not a FreeType font.
+    pub fn itemize(&self, text: &str) -> Result<Vec<(Range<usize>, usize)>, Error> {
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+
+        // Faces are locked one at a time, as fonts sharing a face share its lock.
+        let mut coverage = Vec::with_capacity(self.fonts.len());
+        for font in &self.fonts {
+            let face = font.lock_ft_face()?;
+            coverage.push(
+                chars
+                    .iter()
+                    .map(|&(_, c)| face.has_char(c))
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        let mut runs: Vec<(Range<usize>, usize)> = Vec::new();
+        let mut joined = false;
+        for (k, &(start, c)) in chars.iter().enumerate() {
+            let font = match runs.last() {
+                Some(&(_, font)) if joined || is_continuation(c) => font,
+                Some(&(_, font)) if c.is_whitespace() && coverage[font][k] => font,
+                _ => (0..self.fonts.len())
+                    .find(|&font| coverage[font][k])
+                    .unwrap_or(0),
+            };
+            joined = c == '\u{200d}';
+
+            let end = start + c.len_utf8();
+            match runs.last_mut() {
+                Some(run) if run.1 == font => run.0.end = end,
+                _ => runs.push((start..end, font)),
+            }
+        }
+
+        Ok(runs)
+    }
+
+    /// Converts `text` to glyphs with the fonts of the chain, starting at (`x`, `y`). All runs
+    /// share the baseline `y`.
+    pub fn text_to_glyphs(&self, x: f64, y: f64, text: &str) -> Result<Vec<FallbackRun>, Error> {
+        let mut pen_x = x;
+        self.itemize(text)?
+            .into_iter()
+            .map(|(range, font)| {
+                let scaled_font = &self.fonts[font];
+                let (glyphs, clusters) = scaled_font.text_to_glyphs(pen_x, y, &text[range.clone()]);
+                let x_advance = scaled_font.glyph_extents(&glyphs).x_advance;
+                pen_x += x_advance;
+                Ok(FallbackRun {
+                    range,
+                    font,
+                    glyphs,
+                    clusters,
+                    x_advance,
+                })
+            })
+            .collect()
+    }
+
+    /// Measures `text` as shown by `show_text`, like `ScaledFont::text_extents`.
+    pub fn text_extents(&self, text: &str) -> Result<TextExtents, Error> {
+        let runs = self.text_to_glyphs(0.0, 0.0, text)?;
+
+        let (mut x0, mut y0) = (std::f64::INFINITY, std::f64::INFINITY);
+        let (mut x1, mut y1) = (std::f64::NEG_INFINITY, std::f64::NEG_INFINITY);
+        let mut x_advance = 0.0;
+        for run in &runs {
+            let extents = self.fonts[run.font].glyph_extents(&run.glyphs);
+            if extents.width > 0.0 && extents.height > 0.0 {
+                let first = &run.glyphs[0];
+                x0 = x0.min(first.x + extents.x_bearing);
+                y0 = y0.min(first.y + extents.y_bearing);
+                x1 = x1.max(first.x + extents.x_bearing + extents.width);
+                y1 = y1.max(first.y + extents.y_bearing + extents.height);
+            }
+            x_advance += run.x_advance;
+        }
+
+        if x0 > x1 {
+            x0 = 0.0;
+            y0 = 0.0;
+            x1 = 0.0;
+            y1 = 0.0;
+        }
+        Ok(TextExtents {
+            x_bearing: x0,
+            y_bearing: y0,
+            width: x1 - x0,
+            height: y1 - y0,
+            x_advance,
+            y_advance: 0.0,
+        })
+    }
+
+    /// Shows `text` at the current point of `cr` like `Context::show_text`, switching fonts
+    /// between runs. The font of `cr` is left unchanged.
+    pub fn show_text(&self, cr: &Context, text: &str) -> Result<(), Error> {
+        let (x, y) = if cr.has_current_point() {
+            cr.get_current_point()
+        } else {
+            (0.0, 0.0)
+        };
+        let runs = self.text_to_glyphs(x, y, text)?;
+
+        let scaled_font = cr.get_scaled_font();
+        let mut x_advance = 0.0;
+        for run in &runs {
+            cr.set_scaled_font(&self.fonts[run.font]);
+            cr.show_text_glyphs(
+                &text[run.range.clone()],
+                &run.glyphs,
+                &run.clusters,
+                TextClusterFlags::None,
+            );
+            x_advance += run.x_advance;
+        }
+        cr.set_scaled_font(&scaled_font);
+        cr.move_to(x + x_advance, y);
+
+        cr.status()
+    }
+}
+
+// Characters that belong to the cluster of the character before them.
+fn is_continuation(c: char) -> bool {
+    match c {
+        '\u{300}'..='\u{36f}'
+        | '\u{1ab0}'..='\u{1aff}'
+        | '\u{1dc0}'..='\u{1dff}'
+        | '\u{200c}'..='\u{200d}'
+        | '\u{20d0}'..='\u{20ff}'
+        | '\u{fe00}'..='\u{fe0f}'
+        | '\u{fe20}'..='\u{fe2f}'
+        | '\u{1f3fb}'..='\u{1f3ff}'
+        | '\u{e0020}'..='\u{e007f}'
+        | '\u{e0100}'..='\u{e01ef}' => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enums::{FontSlant, FontWeight, Format};
+    use font::FontconfigQuery;
+    use image_surface::ImageSurface;
+    use libc::c_ulong;
+
+    // Hebrew alef, which only the second font of `fallback_chain` has a glyph for.
+    const ALEF: char = '\u{5d0}';
+
+    fn chain() -> FontFallbackChain {
+        let faces = [
+            FontFace::toy_create("sans-serif", FontSlant::Normal, FontWeight::Normal),
+            FontFace::toy_create("serif", FontSlant::Normal, FontWeight::Bold),
+        ];
+        let mut font_matrix = Matrix::identity();
+        font_matrix.scale(12.0, 12.0);
+        FontFallbackChain::new(
+            &faces,
+            &font_matrix,
+            &Matrix::identity(),
+            &FontOptions::new(),
+        )
+    }
+
+    fn fallback_chain() -> FontFallbackChain {
+        let options = FontOptions::new();
+        let mut font_matrix = Matrix::identity();
+        font_matrix.scale(12.0, 12.0);
+        let fonts: Vec<ScaledFont> = FontconfigQuery::list_families()
+            .unwrap()
+            .iter()
+            .filter_map(|family| {
+                FontFace::from_fontconfig(&FontconfigQuery::new(family), &options).ok()
+            })
+            .map(|face| ScaledFont::new(&face, &font_matrix, &Matrix::identity(), &options))
+            .collect();
+        let covers = |font: &ScaledFont, c| font.lock_ft_face().unwrap().has_char(c);
+
+        let first = fonts
+            .iter()
+            .find(|font| covers(font, 'a') && !covers(font, ALEF))
+            .expect("No installed font without Hebrew");
+        let second = fonts
+            .iter()
+            .find(|font| covers(font, ALEF))
+            .expect("No installed font with Hebrew");
+        FontFallbackChain::from_scaled_fonts(vec![first.clone(), second.clone()])
+    }
+
+    #[test]
+    fn continuation() {
+        assert!(is_continuation('\u{301}'));
+        assert!(is_continuation('\u{fe0f}'));
+        assert!(!is_continuation('e'));
+    }
+
+    #[test]
+    fn covered_text_uses_first_font() {
+        let chain = chain();
+        assert_eq!(chain.itemize("Hello world").unwrap(), [(0..11, 0)]);
+        assert_eq!(chain.itemize("").unwrap(), []);
+        // Nothing covers this private use character, so it stays with the first font.
+        assert_eq!(chain.itemize("a\u{10fffd}b").unwrap(), [(0..6, 0)]);
+    }
+
+    #[test]
+    fn runs_share_baseline_and_advance() {
+        let chain = chain();
+        let runs = chain.text_to_glyphs(3.0, 15.0, "abc").unwrap();
+        assert_eq!(runs.len(), 1);
+        assert!(runs[0].glyphs.iter().all(|g| g.y == 15.0));
+        assert_eq!(runs[0].glyphs[0].x, 3.0);
+
+        let extents = chain.text_extents("abc").unwrap();
+        assert_eq!(extents.x_advance, runs[0].x_advance);
+        assert!(extents.width > 0.0 && extents.y_bearing < 0.0);
+
+        let font_extents = chain.extents();
+        for font in chain.get_scaled_fonts() {
+            assert!(font.extents().ascent <= font_extents.ascent);
+        }
+    }
+
+    #[test]
+    fn show_text_advances_current_point() {
+        let chain = chain();
+        let surface = ImageSurface::create(Format::ARgb32, 100, 30).unwrap();
+        let cr = Context::new(&surface);
+        cr.move_to(2.0, 20.0);
+        chain.show_text(&cr, "abc").unwrap();
+        let advance = chain.text_extents("abc").unwrap().x_advance;
+        let (x, y) = cr.get_current_point();
+        assert!((x - 2.0 - advance).abs() < 1e-9);
+        assert_eq!(y, 20.0);
+    }
+
+    #[test]
+    fn uncovered_text_falls_back() {
+        let chain = fallback_chain();
+        let text = "ab\u{5d0}c";
+        assert_eq!(
+            chain.itemize(text).unwrap(),
+            [(0..2, 0), (2..4, 1), (4..5, 0)]
+        );
+
+        let runs = chain.text_to_glyphs(3.0, 15.0, text).unwrap();
+        let fonts: Vec<_> = runs.iter().map(|run| run.font).collect();
+        assert_eq!(fonts, [0, 1, 0]);
+        let alef = chain.get_scaled_fonts()[1]
+            .lock_ft_face()
+            .unwrap()
+            .get_char_index(ALEF)
+            .unwrap();
+        assert_eq!(runs[1].glyphs.len(), 1);
+        assert_eq!(runs[1].glyphs[0].index, alef as c_ulong);
+
+        // Each run starts where the one before it ends.
+        assert_eq!(runs[0].glyphs[0].x, 3.0);
+        for pair in runs.windows(2) {
+            assert!(pair[0].x_advance > 0.0);
+            let end = pair[0].glyphs[0].x + pair[0].x_advance;
+            assert!((pair[1].glyphs[0].x - end).abs() < 1e-9);
+        }
+        assert!(runs.iter().flat_map(|run| &run.glyphs).all(|g| g.y == 15.0));
+
+        let advance: f64 = runs.iter().map(|run| run.x_advance).sum();
+        assert!((chain.text_extents(text).unwrap().x_advance - advance).abs() < 1e-9);
+
+        let surface = ImageSurface::create(Format::ARgb32, 100, 30).unwrap();
+        let cr = Context::new(&surface);
+        cr.move_to(3.0, 15.0);
+        chain.show_text(&cr, text).unwrap();
+        assert!((cr.get_current_point().0 - 3.0 - advance).abs() < 1e-9);
+    }
+}
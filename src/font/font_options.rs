@@ -4,9 +4,13 @@ use glib::translate::*;
 use std::cmp::PartialEq;
 use std::hash;
 
+#[cfg(any(feature = "v1_16", feature = "dox"))]
+use error::Error;
 #[cfg(any(feature = "v1_16", feature = "dox"))]
 use font::font_face::to_optional_string;
 #[cfg(any(feature = "v1_16", feature = "dox"))]
+use font::FontVariations;
+#[cfg(any(feature = "v1_16", feature = "dox"))]
 use std::ffi::CString;
 #[cfg(not(feature = "use_glib"))]
 use std::ptr;
@@ -125,6 +129,26 @@ impl FontOptions {
             }
         }
     }
+
+    /// Returns the font variations as a `FontVariations`, which is empty if they are unset.
+    ///
+    /// Returns `Error::InvalidString` if the variations cannot be parsed.
+    #[cfg(any(feature = "v1_16", feature = "dox"))]
+    pub fn get_font_variations(&self) -> Result<FontVariations, Error> {
+        match self.get_variations() {
+            Some(variations) => variations.parse(),
+            None => Ok(FontVariations::new()),
+        }
+    }
+
+    #[cfg(any(feature = "v1_16", feature = "dox"))]
+    pub fn set_font_variations(&self, variations: &FontVariations) {
+        if variations.is_empty() {
+            self.set_variations(None);
+        } else {
+            self.set_variations(variations.to_string().as_str());
+        }
+    }
 }
 
 impl PartialEq for FontOptions {
@@ -0,0 +1,183 @@
+// Copyright 2020, The Gtk-rs Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <https://opensource.org/licenses/MIT>
+
+use std::fmt;
+use std::str::FromStr;
+
+use error::Error;
+
+/// OpenType font variation settings, as a list of (axis tag, value) pairs.
+///
+/// This parses from and formats to the syntax of `FontOptions::set_variations`, a
+/// comma-separated list of `tag=value` items such as `"wght=700,wdth=87.5"`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FontVariations {
+    axes: Vec<([u8; 4], f64)>,
+}
+
+impl FontVariations {
+    pub fn new() -> FontVariations {
+        FontVariations::default()
+    }
+
+    /// Sets the value of the `tag` axis, replacing any previous value.
+    pub fn set(&mut self, tag: [u8; 4], value: f64) {
+        match self.axes.iter_mut().find(|&&mut (t, _)| t == tag) {
+            Some(axis) => axis.1 = value,
+            None => self.axes.push((tag, value)),
+        }
+    }
+
+    pub fn get(&self, tag: [u8; 4]) -> Option<f64> {
+        self.axes.iter().find(|&&(t, _)| t == tag).map(|&(_, v)| v)
+    }
+
+    pub fn remove(&mut self, tag: [u8; 4]) -> Option<f64> {
+        let index = self.axes.iter().position(|&(t, _)| t == tag)?;
+        Some(self.axes.remove(index).1)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ([u8; 4], f64)> + '_ {
+        self.axes.iter().cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.axes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.axes.is_empty()
+    }
+}
+
+impl FromStr for FontVariations {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<FontVariations, Error> {
+        let mut variations = FontVariations::new();
+
+        for item in s.split(',') {
+            let item = item.trim();
+            if item.is_empty() {
+                continue;
+            }
+
+            let (tag, rest) = parse_tag(item)?;
+            let rest = rest.trim_start();
+            // `str::strip_prefix` needs Rust 1.45.
+            #[allow(clippy::manual_strip)]
+            let rest = if rest.starts_with('=') {
+                rest[1..].trim_start()
+            } else {
+                rest
+            };
+            let value = rest.parse::<f64>().map_err(|_| Error::InvalidString)?;
+            variations.set(tag, value);
+        }
+
+        Ok(variations)
+    }
+}
+
+// Reads an axis tag of up to four characters, optionally quoted, padding it with spaces.
+fn parse_tag(item: &str) -> Result<([u8; 4], &str), Error> {
+    let (tag, rest) = match item.chars().next() {
+        Some(quote @ '\'') | Some(quote @ '"') => {
+            let end = item[1..].find(quote).ok_or(Error::InvalidString)? + 1;
+            (&item[1..end], &item[end + 1..])
+        }
+        _ => {
+            let end = item
+                .find(|c: char| c == '=' || c.is_whitespace())
+                .unwrap_or(item.len());
+            (&item[..end], &item[end..])
+        }
+    };
+
+    if tag.is_empty() || tag.len() > 4 || !tag.is_ascii() {
+        return Err(Error::InvalidString);
+    }
+
+    let mut bytes = [b' '; 4];
+    bytes[..tag.len()].copy_from_slice(tag.as_bytes());
+    Ok((bytes, rest))
+}
+
+impl fmt::Display for FontVariations {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, &(tag, value)) in self.axes.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            let tag = String::from_utf8_lossy(&tag);
+            write!(f, "{}={}", tag.trim_end(), value)?;
+        }
+        Ok(())
+    }
+}
+
+/// A variation axis of a FreeType font, as listed by `ScaledFont::get_variation_axes`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontVariationAxis {
+    pub tag: [u8; 4],
+    pub name: String,
+    pub minimum: f64,
+    pub default: f64,
+    pub maximum: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let variations: FontVariations = "wght=700, wdth 87.5,,'opsz'=12".parse().unwrap();
+        assert_eq!(variations.len(), 3);
+        assert_eq!(variations.get(*b"wght"), Some(700.0));
+        assert_eq!(variations.get(*b"wdth"), Some(87.5));
+        assert_eq!(variations.get(*b"opsz"), Some(12.0));
+        assert_eq!(variations.get(*b"slnt"), None);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!("wght".parse::<FontVariations>().is_err());
+        assert!("weight=700".parse::<FontVariations>().is_err());
+        assert!("wght=bold".parse::<FontVariations>().is_err());
+    }
+
+    #[test]
+    fn set_replaces_and_formats() {
+        let mut variations = FontVariations::new();
+        variations.set(*b"wght", 400.0);
+        variations.set(*b"wdth", 75.5);
+        variations.set(*b"wght", 650.0);
+        assert_eq!(variations.to_string(), "wght=650,wdth=75.5");
+
+        assert_eq!(variations.remove(*b"wdth"), Some(75.5));
+        assert_eq!(variations.to_string(), "wght=650");
+        assert_eq!(
+            variations.to_string().parse::<FontVariations>(),
+            Ok(variations)
+        );
+    }
+
+    #[cfg(any(feature = "v1_16", feature = "dox"))]
+    #[test]
+    fn font_options_round_trip() {
+        use font::FontOptions;
+
+        let options = FontOptions::new();
+        assert_eq!(options.get_font_variations(), Ok(FontVariations::new()));
+
+        let mut variations = FontVariations::new();
+        variations.set(*b"wght", 650.0);
+        options.set_font_variations(&variations);
+        assert_eq!(options.get_font_variations(), Ok(variations));
+
+        options.set_variations("wght=bold");
+        assert_eq!(options.get_font_variations(), Err(Error::InvalidString));
+    }
+}
@@ -0,0 +1,390 @@
+// Copyright 2020, The Gtk-rs Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <https://opensource.org/licenses/MIT>
+
+use ffi;
+use libc::{c_char, c_int};
+use std::ffi::{CStr, CString};
+use std::path::PathBuf;
+use std::ptr;
+use std::slice;
+
+use enums::FontSlant;
+use error::Error;
+
+use super::FontOptions;
+
+/// A description of the font to look up through fontconfig.
+///
+/// Fields left at their default value are not added to the pattern, so fontconfig's
+/// configuration and defaults decide them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FontconfigQuery {
+    /// Family names in order of preference, e.g. `["Cantarell", "sans-serif"]`.
+    pub families: Vec<String>,
+    /// OpenType weight, from 1 to 1000 (400 is regular, 700 is bold).
+    pub weight: Option<u16>,
+    pub slant: Option<FontSlant>,
+    /// Width as a percentage of the normal width, from 50 (ultra-condensed) to 200
+    /// (ultra-expanded).
+    pub width: Option<u16>,
+    /// RFC 3066 language tag, e.g. `"en"` or `"zh-tw"`.
+    pub language: Option<String>,
+    /// OpenType feature requests, e.g. `"smcp"` or `"ss01"`.
+    pub features: Vec<String>,
+}
+
+impl FontconfigQuery {
+    pub fn new(family: &str) -> FontconfigQuery {
+        FontconfigQuery {
+            families: vec![family.to_owned()],
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn to_pattern(&self) -> Result<FcPattern, Error> {
+        let pattern = FcPattern::new()?;
+
+        for family in &self.families {
+            pattern.add_string(ffi::FC_FAMILY, family)?;
+        }
+        if let Some(weight) = self.weight {
+            let weight = unsafe { ffi::FcWeightFromOpenType(c_int::from(weight)) };
+            if weight < 0 {
+                return Err(Error::InvalidWeight);
+            }
+            pattern.add_integer(ffi::FC_WEIGHT, weight)?;
+        }
+        if let Some(slant) = self.slant {
+            let slant = match slant {
+                FontSlant::Normal => ffi::FC_SLANT_ROMAN,
+                FontSlant::Italic => ffi::FC_SLANT_ITALIC,
+                FontSlant::Oblique => ffi::FC_SLANT_OBLIQUE,
+                FontSlant::__Unknown(_) => return Err(Error::InvalidSlant),
+            };
+            pattern.add_integer(ffi::FC_SLANT, slant)?;
+        }
+        if let Some(width) = self.width {
+            pattern.add_integer(ffi::FC_WIDTH, c_int::from(width))?;
+        }
+        if let Some(ref language) = self.language {
+            pattern.add_string(ffi::FC_LANG, language)?;
+        }
+        for feature in &self.features {
+            pattern.add_string(ffi::FC_FONT_FEATURES, feature)?;
+        }
+
+        Ok(pattern)
+    }
+
+    // Runs the usual substitution sequence (configuration, then the cairo font options, then
+    // the fontconfig defaults) and returns the best matching font.
+    pub(crate) fn find_match(&self, options: &FontOptions) -> Result<FcPattern, Error> {
+        let pattern = self.to_pattern()?;
+
+        unsafe {
+            if ffi::FcConfigSubstitute(ptr::null_mut(), pattern.as_ptr(), ffi::FC_MATCH_PATTERN)
+                == 0
+            {
+                return Err(Error::NoMemory);
+            }
+            ffi::cairo_ft_font_options_substitute(options.to_raw_none(), pattern.as_ptr());
+            ffi::FcDefaultSubstitute(pattern.as_ptr());
+
+            let mut result = ffi::FC_RESULT_MATCH;
+            let matched = ffi::FcFontMatch(ptr::null_mut(), pattern.as_ptr(), &mut result);
+            match ptr::NonNull::new(matched) {
+                Some(matched) if result == ffi::FC_RESULT_MATCH => Ok(FcPattern(matched)),
+                Some(matched) => {
+                    ffi::FcPatternDestroy(matched.as_ptr());
+                    Err(Error::FileNotFound)
+                }
+                None => Err(Error::FileNotFound),
+            }
+        }
+    }
+}
+
+/// An installed font, as listed by `FontconfigQuery::list`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontInfo {
+    pub family: String,
+    pub style: String,
+    pub full_name: Option<String>,
+    /// OpenType weight, from 1 to 1000 (400 is regular, 700 is bold).
+    pub weight: u16,
+    pub slant: FontSlant,
+    /// Width as a percentage of the normal width.
+    pub width: u16,
+    pub file: PathBuf,
+    /// Index of the face in `file`, for font collections.
+    pub index: i32,
+}
+
+impl FontconfigQuery {
+    /// Lists the installed fonts matching the fields of the query that are set.
+    ///
+    /// Unlike `FontFace::from_fontconfig`, no substitution is done: generic families like
+    /// `"sans-serif"` only match fonts that are actually called that way.
+    pub fn list(&self) -> Result<Vec<FontInfo>, Error> {
+        let objects = [
+            ffi::FC_FAMILY,
+            ffi::FC_STYLE,
+            ffi::FC_FULLNAME,
+            ffi::FC_WEIGHT,
+            ffi::FC_SLANT,
+            ffi::FC_WIDTH,
+            ffi::FC_FILE,
+            ffi::FC_INDEX,
+        ];
+        let mut fonts = Vec::new();
+        self.for_each_font(&objects, |font| unsafe {
+            let file = match get_string(font, ffi::FC_FILE) {
+                Some(file) => file,
+                None => return,
+            };
+            // fontconfig returns -1 for weights it can't map. `Ord::clamp` needs Rust 1.50.
+            #[allow(clippy::manual_clamp)]
+            let weight = get_integer(font, ffi::FC_WEIGHT)
+                .map(|weight| ffi::FcWeightToOpenType(weight))
+                .unwrap_or(400)
+                .max(1)
+                .min(1000);
+            let slant = match get_integer(font, ffi::FC_SLANT) {
+                Some(ffi::FC_SLANT_ITALIC) => FontSlant::Italic,
+                Some(ffi::FC_SLANT_OBLIQUE) => FontSlant::Oblique,
+                _ => FontSlant::Normal,
+            };
+            fonts.push(FontInfo {
+                family: get_string(font, ffi::FC_FAMILY).unwrap_or_default(),
+                style: get_string(font, ffi::FC_STYLE).unwrap_or_default(),
+                full_name: get_string(font, ffi::FC_FULLNAME),
+                weight: weight as u16,
+                slant,
+                width: get_integer(font, ffi::FC_WIDTH).unwrap_or(100) as u16,
+                file: PathBuf::from(file),
+                index: get_integer(font, ffi::FC_INDEX).unwrap_or(0),
+            });
+        })?;
+        Ok(fonts)
+    }
+
+    /// Lists the names of the installed font families, sorted and without duplicates.
+    pub fn list_families() -> Result<Vec<String>, Error> {
+        let mut families = Vec::new();
+        FontconfigQuery::default().for_each_font(&[ffi::FC_FAMILY], |font| unsafe {
+            families.extend(get_string(font, ffi::FC_FAMILY));
+        })?;
+        families.sort();
+        families.dedup();
+        Ok(families)
+    }
+
+    /// Returns whether a font matching the query supports the `language` RFC 3066 tag, e.g.
+    /// `"de"` or `"zh-tw"`. A font for another territory of the same language counts.
+    pub fn supports_language(&self, language: &str) -> Result<bool, Error> {
+        let language = CString::new(language).map_err(|_| Error::InvalidString)?;
+        let mut supported = false;
+        self.for_each_font(&[ffi::FC_LANG], |font| unsafe {
+            let mut lang_set = ptr::null_mut();
+            if ffi::FcPatternGetLangSet(
+                font,
+                ffi::FC_LANG.as_ptr() as *const c_char,
+                0,
+                &mut lang_set,
+            ) == ffi::FC_RESULT_MATCH
+            {
+                let result =
+                    ffi::FcLangSetHasLang(lang_set, language.as_ptr() as *const ffi::FcChar8);
+                supported |= result != ffi::FC_LANG_DIFFERENT_LANG;
+            }
+        })?;
+        Ok(supported)
+    }
+
+    /// Returns whether a single font matching the query has glyphs for all the characters of
+    /// `text`.
+    pub fn supports_text(&self, text: &str) -> Result<bool, Error> {
+        let mut supported = false;
+        self.for_each_font(&[ffi::FC_CHARSET], |font| unsafe {
+            let mut char_set = ptr::null_mut();
+            if ffi::FcPatternGetCharSet(
+                font,
+                ffi::FC_CHARSET.as_ptr() as *const c_char,
+                0,
+                &mut char_set,
+            ) == ffi::FC_RESULT_MATCH
+            {
+                supported |= text
+                    .chars()
+                    .all(|c| ffi::FcCharSetHasChar(char_set, c as ffi::FcChar32) != 0);
+            }
+        })?;
+        Ok(supported)
+    }
+
+    // Calls `f` with every font matching the query, holding the `objects` properties.
+    fn for_each_font<F: FnMut(*mut ffi::FcPattern)>(
+        &self,
+        objects: &[&[u8]],
+        mut f: F,
+    ) -> Result<(), Error> {
+        let pattern = self.to_pattern()?;
+
+        unsafe {
+            let object_set = ffi::FcObjectSetCreate();
+            if object_set.is_null() {
+                return Err(Error::NoMemory);
+            }
+            for object in objects {
+                ffi::FcObjectSetAdd(object_set, object.as_ptr() as *const c_char);
+            }
+            let font_set = ffi::FcFontList(ptr::null_mut(), pattern.as_ptr(), object_set);
+            ffi::FcObjectSetDestroy(object_set);
+            if font_set.is_null() {
+                return Err(Error::NoMemory);
+            }
+
+            if (*font_set).nfont > 0 {
+                for &font in slice::from_raw_parts((*font_set).fonts, (*font_set).nfont as usize) {
+                    f(font);
+                }
+            }
+            ffi::FcFontSetDestroy(font_set);
+        }
+
+        Ok(())
+    }
+}
+
+unsafe fn get_string(pattern: *mut ffi::FcPattern, object: &[u8]) -> Option<String> {
+    let mut value = ptr::null_mut();
+    if ffi::FcPatternGetString(pattern, object.as_ptr() as *const c_char, 0, &mut value)
+        != ffi::FC_RESULT_MATCH
+    {
+        return None;
+    }
+    Some(
+        CStr::from_ptr(value as *const c_char)
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
+unsafe fn get_integer(pattern: *mut ffi::FcPattern, object: &[u8]) -> Option<c_int> {
+    let mut value = 0;
+    if ffi::FcPatternGetInteger(pattern, object.as_ptr() as *const c_char, 0, &mut value)
+        == ffi::FC_RESULT_MATCH
+    {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+// Owned reference to a fontconfig pattern.
+#[derive(Debug)]
+pub(crate) struct FcPattern(ptr::NonNull<ffi::FcPattern>);
+
+impl FcPattern {
+    pub(crate) fn new() -> Result<FcPattern, Error> {
+        let ptr = unsafe { ffi::FcPatternCreate() };
+        ptr::NonNull::new(ptr).map(FcPattern).ok_or(Error::NoMemory)
+    }
+
+    pub(crate) fn as_ptr(&self) -> *mut ffi::FcPattern {
+        self.0.as_ptr()
+    }
+
+    pub(crate) fn add_string(&self, object: &[u8], value: &str) -> Result<(), Error> {
+        let value = CString::new(value).map_err(|_| Error::InvalidString)?;
+        let added = unsafe {
+            ffi::FcPatternAddString(
+                self.as_ptr(),
+                object.as_ptr() as *const c_char,
+                value.as_ptr() as *const ffi::FcChar8,
+            )
+        };
+        if added != 0 {
+            Ok(())
+        } else {
+            Err(Error::NoMemory)
+        }
+    }
+
+    pub(crate) fn add_integer(&self, object: &[u8], value: c_int) -> Result<(), Error> {
+        let added = unsafe {
+            ffi::FcPatternAddInteger(self.as_ptr(), object.as_ptr() as *const c_char, value)
+        };
+        if added != 0 {
+            Ok(())
+        } else {
+            Err(Error::NoMemory)
+        }
+    }
+}
+
+impl Drop for FcPattern {
+    fn drop(&mut self) {
+        unsafe { ffi::FcPatternDestroy(self.as_ptr()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enums::FontType;
+    use font::FontFace;
+
+    #[test]
+    fn query_with_nul_is_invalid() {
+        let query = FontconfigQuery::new("Sans\0Serif");
+        assert_eq!(query.to_pattern().unwrap_err(), Error::InvalidString);
+    }
+
+    #[test]
+    fn from_fontconfig_is_ft_face() {
+        let query = FontconfigQuery {
+            families: vec!["sans-serif".to_owned()],
+            weight: Some(700),
+            slant: Some(FontSlant::Italic),
+            language: Some("en".to_owned()),
+            ..Default::default()
+        };
+        let face = FontFace::from_fontconfig(&query, &FontOptions::new()).unwrap();
+        assert_eq!(face.get_type(), FontType::FontTypeFt);
+    }
+
+    #[test]
+    fn list_installed_fonts() {
+        let families = FontconfigQuery::list_families().unwrap();
+        assert!(!families.is_empty());
+        assert!(families.windows(2).all(|w| w[0] < w[1]));
+
+        let query = FontconfigQuery::new(&families[0]);
+        let fonts = query.list().unwrap();
+        assert!(!fonts.is_empty());
+        for font in &fonts {
+            assert!(font.file.exists());
+            assert!(font.weight > 0 && font.weight <= 1000);
+        }
+
+        let missing = FontconfigQuery::new("No Such Font Family \u{1f984}");
+        assert!(missing.list().unwrap().is_empty());
+        assert!(!missing.supports_text("a").unwrap());
+        assert!(!missing.supports_language("en").unwrap());
+    }
+
+    #[test]
+    fn language_and_text_support() {
+        let latin = FontconfigQuery {
+            language: Some("en".to_owned()),
+            ..Default::default()
+        };
+        let family = latin.list().unwrap().remove(0).family;
+        let query = FontconfigQuery::new(&family);
+        assert!(query.supports_language("en").unwrap());
+        assert!(query.supports_text("Hello").unwrap());
+        assert!(!query.supports_text("\u{10fffd}").unwrap());
+    }
+}
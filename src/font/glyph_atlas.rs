@@ -0,0 +1,329 @@
+// Copyright 2020, The Gtk-rs Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <https://opensource.org/licenses/MIT>
+
+use std::collections::HashMap;
+
+use context::Context;
+use enums::Format;
+use error::Error;
+use ffi::Glyph;
+use image_surface::ImageSurface;
+use rectangle_int::RectangleInt;
+
+use super::ScaledFont;
+
+/// Places rectangles left to right on shelves stacked from top to bottom.
+///
+/// Every rectangle is kept `padding` pixels away from its neighbours and from the edges.
+#[derive(Clone, Debug)]
+pub struct ShelfPacker {
+    width: i32,
+    padding: i32,
+    height: i32,
+    shelves: Vec<Shelf>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Shelf {
+    y: i32,
+    height: i32,
+    x: i32,
+}
+
+impl ShelfPacker {
+    pub fn new(width: i32, padding: i32) -> ShelfPacker {
+        ShelfPacker {
+            width,
+            padding,
+            height: padding,
+            shelves: Vec::new(),
+        }
+    }
+
+    /// Reserves a `width` x `height` area and returns its position, or `None` if it is wider
+    /// than the packer.
+    ///
+    /// Packing is tighter when rectangles are added in order of decreasing height.
+    pub fn pack(&mut self, width: i32, height: i32) -> Option<RectangleInt> {
+        if width < 0 || height < 0 || width + 2 * self.padding > self.width {
+            return None;
+        }
+
+        let padding = self.padding;
+        let max_x = self.width - padding;
+        let shelf = match self
+            .shelves
+            .iter_mut()
+            .find(|shelf| shelf.height >= height && shelf.x + width <= max_x)
+        {
+            Some(shelf) => shelf,
+            None => {
+                self.shelves.push(Shelf {
+                    y: self.height,
+                    height,
+                    x: padding,
+                });
+                self.height += height + padding;
+                self.shelves.last_mut().unwrap()
+            }
+        };
+
+        let rectangle = RectangleInt {
+            x: shelf.x,
+            y: shelf.y,
+            width,
+            height,
+        };
+        shelf.x += width + padding;
+        Some(rectangle)
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    /// Returns the height used so far, including the padding below the last shelf.
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+}
+
+/// Placement and metrics of one glyph in a `GlyphAtlas`, in device pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AtlasGlyph {
+    pub index: u64,
+    /// Area of the atlas holding the glyph. It is empty for glyphs without ink, like spaces.
+    pub rect: RectangleInt,
+    /// Offset from the glyph origin to the left edge of `rect`.
+    pub x_bearing: f64,
+    /// Offset from the glyph origin to the top edge of `rect`.
+    pub y_bearing: f64,
+    pub x_advance: f64,
+    pub y_advance: f64,
+}
+
+/// Glyphs of a `ScaledFont` rasterized by cairo into a single `Format::A8` image.
+#[derive(Debug)]
+pub struct GlyphAtlas {
+    surface: ImageSurface,
+    glyphs: Vec<AtlasGlyph>,
+    lookup: HashMap<u64, usize>,
+}
+
+impl GlyphAtlas {
+    /// Renders `glyph_indices` into an atlas `width` pixels wide, `padding` pixels apart.
+    ///
+    /// Glyphs are rendered in the device space of the font's CTM, with the font's options.
+    pub fn build(
+        scaled_font: &ScaledFont,
+        glyph_indices: &[u64],
+        width: i32,
+        padding: i32,
+    ) -> Result<GlyphAtlas, Error> {
+        let mut ctm = scaled_font.get_ctm();
+        ctm.x0 = 0.0;
+        ctm.y0 = 0.0;
+        let inverse_ctm = ctm.try_invert()?;
+
+        let mut glyphs = Vec::new();
+        let mut lookup = HashMap::new();
+        for &index in glyph_indices {
+            if lookup.contains_key(&index) {
+                continue;
+            }
+            let extents = scaled_font.glyph_extents(&[Glyph {
+                index: index as _,
+                x: 0.0,
+                y: 0.0,
+            }]);
+
+            // Device space bounding box of the user space ink rectangle.
+            let (mut x0, mut y0) = (std::f64::INFINITY, std::f64::INFINITY);
+            let (mut x1, mut y1) = (std::f64::NEG_INFINITY, std::f64::NEG_INFINITY);
+            for &(x, y) in &[
+                (extents.x_bearing, extents.y_bearing),
+                (extents.x_bearing + extents.width, extents.y_bearing),
+                (extents.x_bearing, extents.y_bearing + extents.height),
+                (
+                    extents.x_bearing + extents.width,
+                    extents.y_bearing + extents.height,
+                ),
+            ] {
+                let (x, y) = ctm.transform_distance(x, y);
+                x0 = x0.min(x);
+                y0 = y0.min(y);
+                x1 = x1.max(x);
+                y1 = y1.max(y);
+            }
+            let (x_advance, y_advance) =
+                ctm.transform_distance(extents.x_advance, extents.y_advance);
+
+            let rect = if extents.width > 0.0 && extents.height > 0.0 {
+                RectangleInt {
+                    x: 0,
+                    y: 0,
+                    width: (x1.ceil() - x0.floor()) as i32,
+                    height: (y1.ceil() - y0.floor()) as i32,
+                }
+            } else {
+                RectangleInt {
+                    x: 0,
+                    y: 0,
+                    width: 0,
+                    height: 0,
+                }
+            };
+
+            lookup.insert(index, glyphs.len());
+            glyphs.push(AtlasGlyph {
+                index,
+                rect,
+                x_bearing: x0.floor(),
+                y_bearing: y0.floor(),
+                x_advance,
+                y_advance,
+            });
+        }
+
+        let mut order: Vec<usize> = (0..glyphs.len())
+            .filter(|&i| glyphs[i].rect.width > 0)
+            .collect();
+        order.sort_by(|&a, &b| glyphs[b].rect.height.cmp(&glyphs[a].rect.height));
+
+        let mut packer = ShelfPacker::new(width, padding);
+        for &i in &order {
+            let rect = &mut glyphs[i].rect;
+            *rect = packer
+                .pack(rect.width, rect.height)
+                .ok_or(Error::InvalidSize)?;
+        }
+
+        let surface = ImageSurface::create(Format::A8, width, packer.height().max(1))?;
+        {
+            let cr = Context::new(&surface);
+            cr.set_matrix(ctm);
+            cr.set_scaled_font(scaled_font);
+            let positioned: Vec<Glyph> = order
+                .iter()
+                .map(|&i| {
+                    let glyph = &glyphs[i];
+                    let (x, y) = inverse_ctm.transform_distance(
+                        f64::from(glyph.rect.x) - glyph.x_bearing,
+                        f64::from(glyph.rect.y) - glyph.y_bearing,
+                    );
+                    Glyph {
+                        index: glyph.index as _,
+                        x,
+                        y,
+                    }
+                })
+                .collect();
+            cr.show_glyphs(&positioned);
+            cr.status()?;
+        }
+        surface.flush();
+
+        Ok(GlyphAtlas {
+            surface,
+            glyphs,
+            lookup,
+        })
+    }
+
+    pub fn get_surface(&self) -> &ImageSurface {
+        &self.surface
+    }
+
+    pub fn into_surface(self) -> ImageSurface {
+        self.surface
+    }
+
+    /// Returns the atlas entry of the glyph `index`, if it was requested.
+    pub fn get_glyph(&self, index: u64) -> Option<&AtlasGlyph> {
+        self.lookup.get(&index).map(|&i| &self.glyphs[i])
+    }
+
+    /// Returns all entries, in the order their glyphs were first requested.
+    pub fn glyphs(&self) -> &[AtlasGlyph] {
+        &self.glyphs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enums::{FontSlant, FontWeight};
+    use font::{FontFace, FontOptions};
+    use matrices::Matrix;
+
+    fn overlaps(a: &RectangleInt, b: &RectangleInt) -> bool {
+        a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+    }
+
+    #[test]
+    fn shelf_packing() {
+        let mut packer = ShelfPacker::new(20, 1);
+        let a = packer.pack(10, 8).unwrap();
+        let b = packer.pack(7, 6).unwrap();
+        let c = packer.pack(5, 5).unwrap();
+        assert_eq!(
+            a,
+            RectangleInt {
+                x: 1,
+                y: 1,
+                width: 10,
+                height: 8
+            }
+        );
+        assert_eq!((b.x, b.y), (12, 1));
+        assert_eq!((c.x, c.y), (1, 10));
+        assert_eq!(packer.height(), 16);
+        assert!(!overlaps(&a, &b) && !overlaps(&a, &c) && !overlaps(&b, &c));
+        assert!(packer.pack(19, 1).is_none());
+    }
+
+    #[test]
+    fn build_atlas() {
+        let face = FontFace::toy_create("sans-serif", FontSlant::Normal, FontWeight::Normal);
+        let mut font_matrix = Matrix::identity();
+        font_matrix.scale(16.0, 16.0);
+        let scaled_font = ScaledFont::new(
+            &face,
+            &font_matrix,
+            &Matrix::identity(),
+            &FontOptions::new(),
+        );
+        let (glyphs, _) = scaled_font.text_to_glyphs(0.0, 0.0, "Hello, world");
+        let indices: Vec<u64> = glyphs.iter().map(|g| g.index as u64).collect();
+
+        let atlas = GlyphAtlas::build(&scaled_font, &indices, 64, 1).unwrap();
+        let surface = atlas.get_surface();
+        assert_eq!(surface.get_format(), Format::A8);
+        assert_eq!(atlas.glyphs().len(), 9);
+
+        let space = atlas.get_glyph(indices[6]).unwrap();
+        assert_eq!(space.rect.width, 0);
+        assert!(space.x_advance > 0.0);
+
+        let h = *atlas.get_glyph(indices[0]).unwrap();
+        assert!(h.rect.width > 0 && h.rect.x + h.rect.width <= 64);
+        assert!(h.rect.y + h.rect.height <= surface.get_height());
+        for other in atlas.glyphs() {
+            assert!(other.index == h.index || !overlaps(&h.rect, &other.rect));
+        }
+
+        let stride = surface.get_stride() as usize;
+        let mut ink = 0u32;
+        surface
+            .with_data(|data| {
+                for y in h.rect.y..h.rect.y + h.rect.height {
+                    for x in h.rect.x..h.rect.x + h.rect.width {
+                        ink += u32::from(data[y as usize * stride + x as usize]);
+                    }
+                }
+            })
+            .unwrap();
+        assert!(ink > 0);
+    }
+}
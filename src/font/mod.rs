@@ -2,9 +2,21 @@
 // See the COPYRIGHT file at the top-level directory of this distribution.
 // Licensed under the MIT license, see the LICENSE file or <https://opensource.org/licenses/MIT>
 
+mod bidi;
+mod decoration;
 mod font_face;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+mod font_fallback;
 mod font_options;
+mod font_variations;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+mod fontconfig;
+mod glyph_atlas;
+mod paragraph;
+mod rich_text;
 mod scaled_font;
+#[cfg(any(feature = "harfbuzz", feature = "dox"))]
+mod shaping;
 
 pub use enums::{
     Antialias, FontSlant, FontType, FontWeight, HintMetrics, HintStyle, SubpixelOrder,
@@ -39,6 +51,20 @@ impl TextCluster {
 }
 */
 
+pub use self::bidi::{BidiRun, TextDirection};
+pub use self::decoration::{TextDecoration, TextDecorationMetrics};
 pub use self::font_face::FontFace;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub use self::font_fallback::{FallbackRun, FontFallbackChain};
 pub use self::font_options::FontOptions;
+pub use self::font_variations::{FontVariationAxis, FontVariations};
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub use self::fontconfig::{FontInfo, FontconfigQuery};
+pub use self::glyph_atlas::{AtlasGlyph, GlyphAtlas, ShelfPacker};
+pub use self::paragraph::{LayoutLine, LayoutOptions, ParagraphLayout, TextAlignment};
+pub use self::rich_text::{AttributedString, LineExtents, StyledRun, TextStyle};
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub use self::scaled_font::LockedFtFace;
 pub use self::scaled_font::ScaledFont;
+#[cfg(any(feature = "harfbuzz", feature = "dox"))]
+pub use self::shaping::ShapingOptions;
@@ -0,0 +1,405 @@
+// Copyright 2020, The Gtk-rs Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <https://opensource.org/licenses/MIT>
+
+use std::ops::Range;
+
+use unicode_linebreak::{linebreaks, BreakOpportunity};
+
+use context::Context;
+use ffi::Glyph;
+
+use super::ScaledFont;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextAlignment {
+    Left,
+    Right,
+    Center,
+    /// Stretches the spaces of every line but the last one of each paragraph to fill the width.
+    Justify,
+}
+
+/// Settings for `ParagraphLayout::new`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayoutOptions {
+    /// Maximum line width in user space. Lines are not wrapped if it is infinite.
+    pub width: f64,
+    pub alignment: TextAlignment,
+    /// Distance between baselines, as a multiple of the font's `FontExtents::height`.
+    pub line_spacing: f64,
+    /// Maximum number of lines. The last line shown ends with `ellipsis` if text is cut.
+    pub max_lines: Option<usize>,
+    pub ellipsis: String,
+}
+
+impl LayoutOptions {
+    pub fn new(width: f64) -> LayoutOptions {
+        LayoutOptions {
+            width,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for LayoutOptions {
+    fn default() -> LayoutOptions {
+        LayoutOptions {
+            width: std::f64::INFINITY,
+            alignment: TextAlignment::Left,
+            line_spacing: 1.0,
+            max_lines: None,
+            ellipsis: "\u{2026}".to_owned(),
+        }
+    }
+}
+
+/// One line of a `ParagraphLayout`.
+#[derive(Clone, Debug)]
+pub struct LayoutLine {
+    /// Byte range of the laid out text shown on this line, without trailing whitespace.
+    pub range: Range<usize>,
+    /// Glyphs positioned in the layout's coordinates, ready for `Context::show_glyphs`.
+    pub glyphs: Vec<Glyph>,
+    pub x: f64,
+    pub baseline: f64,
+    pub width: f64,
+    /// Whether the line ends with the ellipsis.
+    pub ellipsized: bool,
+}
+
+/// Text broken into lines at Unicode line break opportunities and aligned within a width.
+///
+/// The layout's origin is its top left corner. The first baseline is at the font's ascent,
+/// and following ones are `line_spacing` font heights apart.
+#[derive(Clone, Debug)]
+pub struct ParagraphLayout {
+    scaled_font: ScaledFont,
+    lines: Vec<LayoutLine>,
+    width: f64,
+    height: f64,
+}
+
+impl ParagraphLayout {
+    pub fn new(scaled_font: &ScaledFont, text: &str, options: &LayoutOptions) -> ParagraphLayout {
+        let measure = |s: &str| scaled_font.text_extents(trim_end(s)).x_advance;
+
+        // (start, end, ends a paragraph)
+        let mut breaks: Vec<(usize, usize, bool)> = Vec::new();
+        let opportunities: Vec<(usize, BreakOpportunity)> = linebreaks(text).collect();
+        let mut start = 0;
+        let mut fitting = None;
+        let mut i = 0;
+        while i < opportunities.len() {
+            let (end, opportunity) = opportunities[i];
+            let mandatory = opportunity == BreakOpportunity::Mandatory;
+            if measure(&text[start..end]) <= options.width {
+                if mandatory {
+                    breaks.push((start, end, true));
+                    start = end;
+                    fitting = None;
+                } else {
+                    fitting = Some(end);
+                }
+                i += 1;
+            } else if let Some(fitting) = fitting.take() {
+                breaks.push((start, fitting, false));
+                start = fitting;
+            } else {
+                // A single word wider than the line, break it between characters.
+                let split = start + fit_prefix(&text[start..end], options.width, &measure);
+                if split == end {
+                    breaks.push((start, end, mandatory));
+                    i += 1;
+                } else {
+                    breaks.push((start, split, false));
+                }
+                start = split;
+            }
+        }
+
+        let mut ellipsize = false;
+        if let Some(max_lines) = options.max_lines {
+            if breaks.len() > max_lines {
+                breaks.truncate(max_lines);
+                ellipsize = true;
+            }
+        }
+
+        let extents = scaled_font.extents();
+        let line_height = extents.height * options.line_spacing;
+        let mut lines: Vec<LayoutLine> = breaks
+            .iter()
+            .enumerate()
+            .map(|(n, &(start, end, hard))| {
+                let baseline = extents.ascent + n as f64 * line_height;
+                let line = &text[start..end];
+                let end = start + trim_end(line).len();
+
+                if ellipsize && n + 1 == breaks.len() {
+                    let ellipsis = &options.ellipsis;
+                    let fits = |s: &str| measure(s) + measure(ellipsis) <= options.width;
+                    let kept = prefix_len(line, fits);
+                    let shown = format!("{}{}", trim_end(&line[..kept]), ellipsis);
+                    let (glyphs, _) = scaled_font.text_to_glyphs(0.0, baseline, &shown);
+                    return LayoutLine {
+                        range: start..start + trim_end(&line[..kept]).len(),
+                        glyphs,
+                        x: 0.0,
+                        baseline,
+                        width: measure(&shown),
+                        ellipsized: true,
+                    };
+                }
+
+                let line = &text[start..end];
+                let (mut glyphs, clusters) = scaled_font.text_to_glyphs(0.0, baseline, line);
+                let mut width = measure(line);
+                if options.alignment == TextAlignment::Justify && !hard && options.width.is_finite()
+                {
+                    let gaps = clusters
+                        .iter()
+                        .scan(0, |offset, cluster| {
+                            let bytes = &line[*offset..*offset + cluster.num_bytes as usize];
+                            *offset += cluster.num_bytes as usize;
+                            Some(bytes.chars().all(char::is_whitespace))
+                        })
+                        .filter(|&space| space)
+                        .count();
+                    if gaps > 0 {
+                        let gap = (options.width - width) / gaps as f64;
+                        let (mut glyph, mut offset, mut shift) = (0, 0, 0.0);
+                        for cluster in &clusters {
+                            let bytes = &line[offset..offset + cluster.num_bytes as usize];
+                            offset += cluster.num_bytes as usize;
+                            for g in &mut glyphs[glyph..glyph + cluster.num_glyphs as usize] {
+                                g.x += shift;
+                            }
+                            glyph += cluster.num_glyphs as usize;
+                            if bytes.chars().all(char::is_whitespace) {
+                                shift += gap;
+                            }
+                        }
+                        width = options.width;
+                    }
+                }
+
+                LayoutLine {
+                    range: start..end,
+                    glyphs,
+                    x: 0.0,
+                    baseline,
+                    width,
+                    ellipsized: false,
+                }
+            })
+            .collect();
+
+        let width = if options.width.is_finite() {
+            options.width
+        } else {
+            lines.iter().map(|line| line.width).fold(0.0, f64::max)
+        };
+        for line in &mut lines {
+            let x = match options.alignment {
+                TextAlignment::Left | TextAlignment::Justify => 0.0,
+                TextAlignment::Right => width - line.width,
+                TextAlignment::Center => (width - line.width) / 2.0,
+            };
+            line.x = x;
+            for glyph in &mut line.glyphs {
+                glyph.x += x;
+            }
+        }
+
+        ParagraphLayout {
+            scaled_font: scaled_font.clone(),
+            height: lines.len() as f64 * line_height,
+            lines,
+            width,
+        }
+    }
+
+    pub fn lines(&self) -> &[LayoutLine] {
+        &self.lines
+    }
+
+    /// Returns the width the lines are aligned in: the requested width, or the width of the
+    /// longest line if it was infinite.
+    pub fn get_width(&self) -> f64 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> f64 {
+        self.height
+    }
+
+    /// Returns the glyphs of all lines.
+    pub fn glyphs(&self) -> Vec<Glyph> {
+        self.lines
+            .iter()
+            .flat_map(|line| line.glyphs.iter().cloned())
+            .collect()
+    }
+
+    /// Draws the layout with its font, its origin at the origin of `cr`'s user space.
+    pub fn show(&self, cr: &Context) {
+        cr.set_scaled_font(&self.scaled_font);
+        for line in &self.lines {
+            cr.show_glyphs(&line.glyphs);
+        }
+    }
+}
+
+fn trim_end(s: &str) -> &str {
+    s.trim_end_matches(char::is_whitespace)
+}
+
+// Returns the length of the longest prefix of `s` for which `fits` holds, assuming that it
+// holds for all prefixes shorter than one that does.
+fn prefix_len<F: Fn(&str) -> bool>(s: &str, fits: F) -> usize {
+    let ends: Vec<usize> = s.char_indices().map(|(i, c)| i + c.len_utf8()).collect();
+    let (mut low, mut high) = (0, ends.len());
+    while low < high {
+        let mid = (low + high + 1) / 2;
+        if fits(&s[..ends[mid - 1]]) {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    if low == 0 {
+        0
+    } else {
+        ends[low - 1]
+    }
+}
+
+// Returns the length of the longest prefix of `s` narrower than `width`, keeping at least one
+// character so that breaking always makes progress.
+fn fit_prefix<F: Fn(&str) -> f64>(s: &str, width: f64, measure: &F) -> usize {
+    let len = prefix_len(s, |prefix| measure(prefix) <= width);
+    if len == 0 {
+        s.chars().next().map_or(0, char::len_utf8)
+    } else {
+        len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enums::{FontSlant, FontWeight};
+    use font::{FontFace, FontOptions};
+    use matrices::Matrix;
+
+    fn scaled_font() -> ScaledFont {
+        let face = FontFace::toy_create("monospace", FontSlant::Normal, FontWeight::Normal);
+        let mut font_matrix = Matrix::identity();
+        font_matrix.scale(10.0, 10.0);
+        ScaledFont::new(
+            &face,
+            &font_matrix,
+            &Matrix::identity(),
+            &FontOptions::new(),
+        )
+    }
+
+    fn line_texts<'a>(layout: &ParagraphLayout, text: &'a str) -> Vec<&'a str> {
+        layout
+            .lines()
+            .iter()
+            .map(|line| &text[line.range.clone()])
+            .collect()
+    }
+
+    #[test]
+    fn prefix_search() {
+        assert_eq!(prefix_len("abcdef", |s| s.len() <= 3), 3);
+        assert_eq!(prefix_len("a\u{e9}b", |s| s.chars().count() <= 2), 3);
+        assert_eq!(prefix_len("abc", |_| false), 0);
+        assert_eq!(fit_prefix("\u{e9}bc", 0.0, &|s: &str| s.len() as f64), 2);
+    }
+
+    #[test]
+    fn wraps_at_spaces_and_newlines() {
+        let font = scaled_font();
+        let text = "the quick brown fox\njumps";
+        let width = font.text_extents("the quick").x_advance + 1.0;
+        let layout = ParagraphLayout::new(&font, text, &LayoutOptions::new(width));
+        assert_eq!(
+            line_texts(&layout, text),
+            ["the quick", "brown fox", "jumps"]
+        );
+        for line in layout.lines() {
+            assert!(line.width <= width);
+        }
+
+        let extents = font.extents();
+        let lines = layout.lines();
+        assert_eq!(lines[0].baseline, extents.ascent);
+        assert!((lines[1].baseline - lines[0].baseline - extents.height).abs() < 1e-9);
+        assert_eq!(layout.get_height(), 3.0 * extents.height);
+    }
+
+    #[test]
+    fn breaks_long_words() {
+        let font = scaled_font();
+        let text = "abcdefgh";
+        let width = font.text_extents("abc").x_advance;
+        let layout = ParagraphLayout::new(&font, text, &LayoutOptions::new(width));
+        assert_eq!(line_texts(&layout, text), ["abc", "def", "gh"]);
+    }
+
+    #[test]
+    fn alignment() {
+        let font = scaled_font();
+        let text = "aaaa bb";
+        let width = font.text_extents("aaaa").x_advance;
+        let mut options = LayoutOptions::new(width);
+
+        options.alignment = TextAlignment::Right;
+        let layout = ParagraphLayout::new(&font, text, &options);
+        let bb = &layout.lines()[1];
+        assert!((bb.x + bb.width - width).abs() < 1e-9);
+        assert_eq!(bb.glyphs[0].x, bb.x);
+
+        options.alignment = TextAlignment::Center;
+        let layout = ParagraphLayout::new(&font, text, &options);
+        let bb = &layout.lines()[1];
+        assert!((2.0 * bb.x + bb.width - width).abs() < 1e-9);
+    }
+
+    #[test]
+    fn justify_stretches_all_but_last_line() {
+        let font = scaled_font();
+        let text = "a b c d";
+        let width = font.text_extents("a b c").x_advance + 5.0;
+        let mut options = LayoutOptions::new(width);
+        options.alignment = TextAlignment::Justify;
+        let layout = ParagraphLayout::new(&font, text, &options);
+        let lines = layout.lines();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].width, width);
+        let plain = font.text_to_glyphs(0.0, 0.0, "a b c").0;
+        assert!((lines[0].glyphs[4].x - plain[4].x - 5.0).abs() < 1e-9);
+        assert!(lines[1].width < width);
+    }
+
+    #[test]
+    fn ellipsize_last_line() {
+        let font = scaled_font();
+        let text = "one two three four";
+        let width = font.text_extents("one two").x_advance;
+        let mut options = LayoutOptions::new(width);
+        options.max_lines = Some(1);
+        options.ellipsis = "...".to_owned();
+        let layout = ParagraphLayout::new(&font, text, &options);
+        let lines = layout.lines();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].ellipsized);
+        assert_eq!(&text[lines[0].range.clone()], "one");
+        assert!(lines[0].width <= width);
+        assert_eq!(lines[0].glyphs.len(), 6);
+    }
+}
@@ -0,0 +1,475 @@
+// Copyright 2020, The Gtk-rs Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <https://opensource.org/licenses/MIT>
+
+use std::ops::Range;
+
+use context::Context;
+use enums::{FontSlant, FontType, FontWeight};
+use error::Error;
+use ffi::Glyph;
+use matrices::Matrix;
+use Rgba;
+
+#[cfg(any(feature = "freetype", feature = "dox"))]
+use super::{font_face::to_optional_string, FontconfigQuery};
+use super::{FontFace, FontOptions, ScaledFont};
+#[cfg(any(feature = "freetype", feature = "dox"))]
+use libc::c_long;
+
+/// Attributes of a span of an `AttributedString`.
+#[derive(Clone, Debug)]
+pub struct TextStyle {
+    pub font_face: FontFace,
+    /// Font size in user space.
+    pub size: f64,
+    pub color: Rgba,
+    /// Distance the span is raised above the baseline in user space, negative to lower it.
+    pub baseline_shift: f64,
+}
+
+impl TextStyle {
+    /// Creates an opaque black style without baseline shift.
+    pub fn new(font_face: &FontFace, size: f64) -> TextStyle {
+        TextStyle {
+            font_face: font_face.clone(),
+            size,
+            color: Rgba::rgb(0.0, 0.0, 0.0),
+            baseline_shift: 0.0,
+        }
+    }
+}
+
+/// Text made of spans with their own font, size, color and baseline shift, laid out on a
+/// single baseline.
+#[derive(Clone, Debug, Default)]
+pub struct AttributedString {
+    text: String,
+    spans: Vec<(Range<usize>, TextStyle)>,
+}
+
+/// A span of an `AttributedString` converted to glyphs.
+#[derive(Clone, Debug)]
+pub struct StyledRun {
+    /// Byte range of the span in the text.
+    pub range: Range<usize>,
+    pub scaled_font: ScaledFont,
+    pub color: Rgba,
+    pub glyphs: Vec<Glyph>,
+    pub x_advance: f64,
+}
+
+/// Metrics of an `AttributedString`, returned by `AttributedString::extents`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineExtents {
+    /// Largest distance from the baseline to the top of the spans.
+    pub ascent: f64,
+    /// Largest distance from the baseline to the bottom of the spans.
+    pub descent: f64,
+    /// Largest recommended line height of the fonts of the spans.
+    pub height: f64,
+    /// Sum of the horizontal advances of the spans.
+    pub advance: f64,
+}
+
+impl AttributedString {
+    pub fn new() -> AttributedString {
+        AttributedString::default()
+    }
+
+    /// Appends `text` with `style`.
+    pub fn push(&mut self, text: &str, style: &TextStyle) {
+        if text.is_empty() {
+            return;
+        }
+        let start = self.text.len();
+        self.text.push_str(text);
+        self.spans.push((start..self.text.len(), style.clone()));
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the spans in order, with their text.
+    pub fn spans(&self) -> impl Iterator<Item = (&str, &TextStyle)> + '_ {
+        self.spans
+            .iter()
+            .map(move |(range, style)| (&self.text[range.clone()], style))
+    }
+
+    /// Converts the spans to glyphs, one after the other from (`x`, `y`), `y` being the
+    /// baseline of unshifted spans. Fonts are created with `ctm` and `options`.
+    pub fn layout(&self, x: f64, y: f64, ctm: &Matrix, options: &FontOptions) -> Vec<StyledRun> {
+        let mut pen_x = x;
+        self.spans
+            .iter()
+            .map(|(range, style)| {
+                let scaled_font = style.scaled_font(ctm, options);
+                let (glyphs, _) = scaled_font.text_to_glyphs(
+                    pen_x,
+                    y - style.baseline_shift,
+                    &self.text[range.clone()],
+                );
+                let x_advance = scaled_font.glyph_extents(&glyphs).x_advance;
+                pen_x += x_advance;
+                StyledRun {
+                    range: range.clone(),
+                    scaled_font,
+                    color: style.color,
+                    glyphs,
+                    x_advance,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the metrics of the line: the largest ascent and descent of the spans, counting
+    /// their baseline shifts, and the total advance.
+    pub fn extents(&self, ctm: &Matrix, options: &FontOptions) -> LineExtents {
+        let mut extents = LineExtents {
+            ascent: 0.0,
+            descent: 0.0,
+            height: 0.0,
+            advance: 0.0,
+        };
+        for (range, style) in &self.spans {
+            let scaled_font = style.scaled_font(ctm, options);
+            let font_extents = scaled_font.extents();
+            extents.ascent = extents
+                .ascent
+                .max(font_extents.ascent + style.baseline_shift);
+            extents.descent = extents
+                .descent
+                .max(font_extents.descent - style.baseline_shift);
+            extents.height = extents.height.max(font_extents.height);
+            extents.advance += scaled_font
+                .text_extents(&self.text[range.clone()])
+                .x_advance;
+        }
+        extents
+    }
+
+    /// Shows the text at the current point of `cr`, leaving its source and font unchanged and
+    /// moving the current point to the end of the text.
+    pub fn show(&self, cr: &Context) {
+        let (x, y) = if cr.has_current_point() {
+            cr.get_current_point()
+        } else {
+            (0.0, 0.0)
+        };
+        let runs = self.layout(x, y, &cr.get_matrix(), &cr.get_font_options());
+
+        cr.save();
+        let mut x_advance = 0.0;
+        for run in &runs {
+            cr.set_source_color(run.color);
+            cr.set_scaled_font(&run.scaled_font);
+            cr.show_glyphs(&run.glyphs);
+            x_advance += run.x_advance;
+        }
+        cr.restore();
+        cr.move_to(x + x_advance, y);
+    }
+
+    /// Parses a small markup language, with `base` as the style of untagged text.
+    ///
+    /// The supported tags are `<b>`, `<i>`, `<sup>`, `<sub>` and `<span color="...">`, with
+    /// any CSS color `Rgba` can parse. Bold and italic text needs a toy font face, or a
+    /// FreeType one whose family is looked up again through fontconfig. The `&lt;`, `&gt;`,
+    /// `&amp;`, `&quot;` and `&apos;` entities are replaced.
+    ///
+    /// Returns `Error::InvalidString` for malformed markup and `Error::FontTypeMismatch`
+    /// for bold or italic text in a font face that is neither a toy nor a FreeType one.
+    pub fn from_markup(markup: &str, base: &TextStyle) -> Result<AttributedString, Error> {
+        let mut string = AttributedString::new();
+        let mut stack: Vec<(String, TextStyle)> = Vec::new();
+        let mut style = base.clone();
+        let mut text = String::new();
+        let mut rest = markup;
+
+        while let Some(c) = rest.chars().next() {
+            match c {
+                '<' => {
+                    let end = rest.find('>').ok_or(Error::InvalidString)?;
+                    let tag = &rest[1..end];
+                    rest = &rest[end + 1..];
+
+                    string.push(&text, &style);
+                    text.clear();
+
+                    // `str::strip_prefix` needs Rust 1.45.
+                    #[allow(clippy::manual_strip)]
+                    let closing = if tag.starts_with('/') {
+                        Some(tag[1..].trim())
+                    } else {
+                        None
+                    };
+                    if let Some(closing) = closing {
+                        match stack.pop() {
+                            Some((name, parent)) if name == closing => style = parent,
+                            _ => return Err(Error::InvalidString),
+                        }
+                    } else {
+                        let name = tag.split_whitespace().next().unwrap_or("");
+                        let nested = apply_tag(&style, name, &tag[name.len()..])?;
+                        stack.push((name.to_owned(), style));
+                        style = nested;
+                    }
+                }
+                '&' => {
+                    let end = rest.find(';').ok_or(Error::InvalidString)?;
+                    text.push(match &rest[1..end] {
+                        "lt" => '<',
+                        "gt" => '>',
+                        "amp" => '&',
+                        "quot" => '"',
+                        "apos" => '\'',
+                        _ => return Err(Error::InvalidString),
+                    });
+                    rest = &rest[end + 1..];
+                }
+                _ => {
+                    text.push(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+
+        if !stack.is_empty() {
+            return Err(Error::InvalidString);
+        }
+        string.push(&text, &style);
+        Ok(string)
+    }
+}
+
+impl TextStyle {
+    fn scaled_font(&self, ctm: &Matrix, options: &FontOptions) -> ScaledFont {
+        let mut font_matrix = Matrix::identity();
+        font_matrix.scale(self.size, self.size);
+        ScaledFont::new(&self.font_face, &font_matrix, ctm, options)
+    }
+
+    // Returns the face of the same family with `slant` or `weight` changed.
+    fn variant(
+        &self,
+        slant: Option<FontSlant>,
+        weight: Option<FontWeight>,
+    ) -> Result<FontFace, Error> {
+        let face = &self.font_face;
+        match face.get_type() {
+            FontType::FontTypeToy => {
+                let family = face.toy_get_family().ok_or(Error::FontTypeMismatch)?;
+                Ok(FontFace::toy_create(
+                    &family,
+                    slant.unwrap_or_else(|| face.toy_get_slant()),
+                    weight.unwrap_or_else(|| face.toy_get_weight()),
+                ))
+            }
+            #[cfg(any(feature = "freetype", feature = "dox"))]
+            FontType::FontTypeFt => self.fontconfig_variant(slant, weight),
+            _ => Err(Error::FontTypeMismatch),
+        }
+    }
+
+    // Looks up the family of a FreeType face through fontconfig, keeping its boldness and
+    // slant unless they are changed.
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    fn fontconfig_variant(
+        &self,
+        slant: Option<FontSlant>,
+        weight: Option<FontWeight>,
+    ) -> Result<FontFace, Error> {
+        let options = FontOptions::new();
+        let scaled_font = self.scaled_font(&Matrix::identity(), &options);
+        let query = {
+            let face = scaled_font.lock_ft_face()?;
+            let family =
+                unsafe { to_optional_string(face.family_name) }.ok_or(Error::FontTypeMismatch)?;
+            let bold =
+                face.style_flags & freetype_crate::freetype::FT_STYLE_FLAG_BOLD as c_long != 0;
+            let italic =
+                face.style_flags & freetype_crate::freetype::FT_STYLE_FLAG_ITALIC as c_long != 0;
+
+            let weight = match weight {
+                Some(weight) => weight,
+                None if bold => FontWeight::Bold,
+                None => FontWeight::Normal,
+            };
+            let slant = match slant {
+                Some(slant) => slant,
+                None if italic => FontSlant::Italic,
+                None => FontSlant::Normal,
+            };
+            FontconfigQuery {
+                weight: Some(if weight == FontWeight::Bold { 700 } else { 400 }),
+                slant: Some(slant),
+                ..FontconfigQuery::new(&family)
+            }
+        };
+        FontFace::from_fontconfig(&query, &options)
+    }
+}
+
+// Returns the style of the content of the `name` tag with `attributes`, inside `style`.
+fn apply_tag(style: &TextStyle, name: &str, attributes: &str) -> Result<TextStyle, Error> {
+    let attributes = attributes.trim();
+    if name != "span" && !attributes.is_empty() {
+        return Err(Error::InvalidString);
+    }
+
+    let mut nested = style.clone();
+    match name {
+        "b" => nested.font_face = style.variant(None, Some(FontWeight::Bold))?,
+        "i" => nested.font_face = style.variant(Some(FontSlant::Italic), None)?,
+        "sup" => {
+            nested.size = style.size * 0.7;
+            nested.baseline_shift = style.baseline_shift + style.size * 0.35;
+        }
+        "sub" => {
+            nested.size = style.size * 0.7;
+            nested.baseline_shift = style.baseline_shift - style.size * 0.15;
+        }
+        "span" => {
+            let value = attributes
+                .trim_start_matches("color")
+                .trim_start()
+                .trim_start_matches('=')
+                .trim();
+            if !attributes.starts_with("color")
+                || value.len() < 2
+                || !value.starts_with('"')
+                || !value.ends_with('"')
+            {
+                return Err(Error::InvalidString);
+            }
+            nested.color = value[1..value.len() - 1].parse()?;
+        }
+        _ => return Err(Error::InvalidString),
+    }
+    Ok(nested)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enums::Format;
+    use image_surface::ImageSurface;
+
+    fn base() -> TextStyle {
+        let face = FontFace::toy_create("sans-serif", FontSlant::Normal, FontWeight::Normal);
+        TextStyle::new(&face, 10.0)
+    }
+
+    fn span_color(color: &str) -> Result<Rgba, Error> {
+        let markup = format!("<span color=\"{}\">x</span>", color);
+        let string = AttributedString::from_markup(&markup, &base())?;
+        let color = string.spans().next().unwrap().1.color;
+        Ok(color)
+    }
+
+    #[test]
+    fn colors() {
+        assert_eq!(span_color("#f00"), Ok(Rgba::rgb(1.0, 0.0, 0.0)));
+        assert_eq!(span_color("#00ff0080").unwrap().alpha, 128.0 / 255.0);
+        assert!(span_color("ff0000").is_err());
+        assert!(span_color("#ff000").is_err());
+    }
+
+    #[test]
+    fn parse_markup() {
+        let string =
+            AttributedString::from_markup("x<sup>2</sup> &lt; <b>y<i>z</i></b>", &base()).unwrap();
+        assert_eq!(string.text(), "x2 < yz");
+
+        let spans: Vec<_> = string.spans().collect();
+        assert_eq!(spans.len(), 5);
+        assert_eq!(spans[1].0, "2");
+        assert_eq!(spans[1].1.size, 7.0);
+        assert_eq!(spans[1].1.baseline_shift, 3.5);
+        assert_eq!(spans[3].1.font_face.toy_get_weight(), FontWeight::Bold);
+        assert_eq!(spans[4].1.font_face.toy_get_weight(), FontWeight::Bold);
+        assert_eq!(spans[4].1.font_face.toy_get_slant(), FontSlant::Italic);
+
+        let string =
+            AttributedString::from_markup("<span color=\"#00f\">blue</span>", &base()).unwrap();
+        assert_eq!(
+            string.spans().next().unwrap().1.color,
+            Rgba::rgb(0.0, 0.0, 1.0)
+        );
+        let string =
+            AttributedString::from_markup("<span color=\"teal\">x</span>", &base()).unwrap();
+        assert_eq!(string.spans().next().unwrap().1.color.to_hex(), "#008080");
+    }
+
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    #[test]
+    fn markup_styles_freetype_faces() {
+        let options = FontOptions::new();
+        let face =
+            FontFace::from_fontconfig(&FontconfigQuery::new("sans-serif"), &options).unwrap();
+        let string =
+            AttributedString::from_markup("a<b>b<i>c</i></b>", &TextStyle::new(&face, 10.0))
+                .unwrap();
+
+        let styles: Vec<_> = string
+            .spans()
+            .map(|(_, style)| {
+                let scaled_font = style.scaled_font(&Matrix::identity(), &options);
+                let flags = scaled_font.lock_ft_face().unwrap().style_flags;
+                (
+                    flags & freetype_crate::freetype::FT_STYLE_FLAG_BOLD as c_long != 0,
+                    flags & freetype_crate::freetype::FT_STYLE_FLAG_ITALIC as c_long != 0,
+                )
+            })
+            .collect();
+        assert_eq!(styles, [(false, false), (true, false), (true, true)]);
+    }
+
+    #[test]
+    fn invalid_markup() {
+        for markup in &[
+            "<b>bold",
+            "<b>x</i>",
+            "</b>",
+            "<u>x</u>",
+            "&nbsp;",
+            "<b",
+            "<b x>y</b>",
+        ] {
+            assert_eq!(
+                AttributedString::from_markup(markup, &base()).unwrap_err(),
+                Error::InvalidString
+            );
+        }
+    }
+
+    #[test]
+    fn layout_on_one_baseline() {
+        let string = AttributedString::from_markup("H<sub>2</sub>O", &base()).unwrap();
+        let runs = string.layout(1.0, 20.0, &Matrix::identity(), &FontOptions::new());
+        assert_eq!(runs.len(), 3);
+        assert_eq!(runs[0].glyphs[0].x, 1.0);
+        assert_eq!(runs[0].glyphs[0].y, 20.0);
+        assert_eq!(runs[1].glyphs[0].y, 21.5);
+        assert_eq!(runs[2].glyphs[0].y, 20.0);
+        assert_eq!(runs[1].glyphs[0].x, 1.0 + runs[0].x_advance);
+
+        let extents = string.extents(&Matrix::identity(), &FontOptions::new());
+        let advance: f64 = runs.iter().map(|run| run.x_advance).sum();
+        assert!((extents.advance - advance).abs() < 1e-9);
+    }
+
+    #[test]
+    fn show_moves_current_point() {
+        let surface = ImageSurface::create(Format::ARgb32, 50, 20).unwrap();
+        let cr = Context::new(&surface);
+        let string = AttributedString::from_markup("a<b>b</b>", &base()).unwrap();
+        cr.move_to(2.0, 15.0);
+        string.show(&cr);
+        let (x, y) = cr.get_current_point();
+        assert!(x > 2.0);
+        assert_eq!(y, 15.0);
+    }
+}
@@ -2,14 +2,28 @@ use ffi;
 #[cfg(feature = "use_glib")]
 use glib::translate::*;
 use std::ffi::CString;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+use std::fmt;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+use std::ops::Deref;
 use std::ptr;
 
+use context::Context;
 use enums::FontType;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+use error::Error;
 use ffi::{FontExtents, Glyph, TextCluster, TextExtents};
 use matrices::Matrix;
+use paths::Path;
 use utils::status_to_result;
 
+#[cfg(any(feature = "freetype", feature = "dox"))]
+use super::{font_face::to_optional_string, FontVariationAxis};
 use super::{FontFace, FontOptions};
+#[cfg(any(feature = "freetype", feature = "dox"))]
+use libc::{c_char, c_long};
+#[cfg(any(feature = "freetype", feature = "dox"))]
+use std::slice;
 
 #[cfg(feature = "use_glib")]
 glib_wrapper! {
@@ -197,6 +211,35 @@ impl ScaledFont {
         }
     }
 
+    /// Returns the outline of the glyph `glyph_index`, with its origin at (0, 0).
+    pub fn glyph_outline(&self, glyph_index: u64) -> Path {
+        self.glyphs_outline(&[Glyph {
+            index: glyph_index as _,
+            x: 0.0,
+            y: 0.0,
+        }])
+    }
+
+    /// Returns the outline of `text`, starting at (`x`, `y`).
+    pub fn text_outline(&self, text: &str, x: f64, y: f64) -> Path {
+        let (glyphs, _) = self.text_to_glyphs(x, y, text);
+        self.glyphs_outline(&glyphs)
+    }
+
+    /// Returns the outline of positioned glyphs, such as those returned by `text_to_glyphs`.
+    pub fn glyphs_outline(&self, glyphs: &[Glyph]) -> Path {
+        // The matrix of the context has to match the font's CTM for cairo to use this scaled
+        // font as is.
+        let cr = Context::scratch();
+        let mut ctm = self.get_ctm();
+        ctm.x0 = 0.0;
+        ctm.y0 = 0.0;
+        cr.set_matrix(ctm);
+        cr.set_scaled_font(self);
+        cr.glyph_path(glyphs);
+        cr.copy_path()
+    }
+
     pub fn get_font_face(&self) -> FontFace {
         unsafe { FontFace::from_raw_none(ffi::cairo_scaled_font_get_font_face(self.to_raw_none())) }
     }
@@ -235,6 +278,62 @@ impl ScaledFont {
         matrix
     }
 
+    /// Locks the FreeType face behind this font and gives access to it until the returned
+    /// guard is dropped.
+    ///
+    /// Returns `Error::FontTypeMismatch` if this is not a FreeType font.
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn lock_ft_face(&self) -> Result<LockedFtFace<'_>, Error> {
+        if self.get_type() != FontType::FontTypeFt {
+            return Err(Error::FontTypeMismatch);
+        }
+
+        let face = unsafe { ffi::cairo_ft_scaled_font_lock_face(self.to_raw_none()) };
+        if face.is_null() {
+            let status = unsafe { ffi::cairo_scaled_font_status(self.to_raw_none()) };
+            status_to_result(status)?;
+            return Err(Error::FreetypeError);
+        }
+
+        Ok(LockedFtFace {
+            scaled_font: self,
+            face: face as freetype_crate::freetype::FT_Face,
+        })
+    }
+
+    /// Lists the variation axes of the FreeType face behind this font.
+    ///
+    /// Fonts without variations have no axes.
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    pub fn get_variation_axes(&self) -> Result<Vec<FontVariationAxis>, Error> {
+        let face = self.lock_ft_face()?;
+        if face.face_flags & freetype_crate::freetype::FT_FACE_FLAG_MULTIPLE_MASTERS as c_long == 0
+        {
+            return Ok(Vec::new());
+        }
+
+        unsafe {
+            let mut mm_var: *mut ffi::FT_MM_Var = ptr::null_mut();
+            if ffi::FT_Get_MM_Var(face.as_ptr() as ffi::FT_Face, &mut mm_var) != 0 {
+                return Err(Error::FreetypeError);
+            }
+
+            let axes = slice::from_raw_parts((*mm_var).axis, (*mm_var).num_axis as usize)
+                .iter()
+                .map(|axis| FontVariationAxis {
+                    tag: (axis.tag as u32).to_be_bytes(),
+                    name: to_optional_string(axis.name).unwrap_or_default(),
+                    minimum: axis.minimum as f64 / 65536.0,
+                    default: axis.def as f64 / 65536.0,
+                    maximum: axis.maximum as f64 / 65536.0,
+                })
+                .collect();
+
+            ffi::FT_Done_MM_Var((*face.glyph).library as ffi::FT_Library, mm_var);
+            Ok(axes)
+        }
+    }
+
     user_data_methods! {
         ffi::cairo_scaled_font_get_user_data,
         ffi::cairo_scaled_font_set_user_data,
@@ -256,3 +355,215 @@ impl Clone for ScaledFont {
         unsafe { ScaledFont::from_raw_none(self.to_raw_none()) }
     }
 }
+
+/// A FreeType face locked with `ScaledFont::lock_ft_face`.
+///
+/// The face is unlocked when this guard is dropped. It must not be used to change the
+/// face's size or transformation, as cairo relies on them while the face is locked.
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub struct LockedFtFace<'a> {
+    scaled_font: &'a ScaledFont,
+    face: freetype_crate::freetype::FT_Face,
+}
+
+#[cfg(any(feature = "freetype", feature = "dox"))]
+impl<'a> LockedFtFace<'a> {
+    /// Returns the raw `FT_Face`, for use with FreeType functions.
+    pub fn as_ptr(&self) -> freetype_crate::freetype::FT_Face {
+        self.face
+    }
+
+    /// Returns whether the face's character map has a glyph for `c`.
+    pub fn has_char(&self, c: char) -> bool {
+        self.get_char_index(c).is_some()
+    }
+
+    /// Returns the index of the glyph for `c` in the face's character map.
+    pub fn get_char_index(&self, c: char) -> Option<u32> {
+        match unsafe { freetype_crate::freetype::FT_Get_Char_Index(self.face, c as _) } {
+            0 => None,
+            index => Some(index),
+        }
+    }
+
+    /// Returns the kerning between glyphs `left` and `right` from the face's `kern` table,
+    /// in device pixels at the font's size. Faces without one have no kerning.
+    pub fn get_kerning(&self, left: u32, right: u32) -> Result<(f64, f64), Error> {
+        let mut kerning = freetype_crate::freetype::FT_Vector { x: 0, y: 0 };
+        let error = unsafe {
+            freetype_crate::freetype::FT_Get_Kerning(
+                self.face,
+                left,
+                right,
+                freetype_crate::freetype::FT_Kerning_Mode::FT_KERNING_UNFITTED as _,
+                &mut kerning,
+            )
+        };
+        if error != 0 {
+            return Err(Error::FreetypeError);
+        }
+        Ok((kerning.x as f64 / 64.0, kerning.y as f64 / 64.0))
+    }
+
+    /// Returns the PostScript name of glyph `index`, if the face has glyph names.
+    pub fn get_glyph_name(&self, index: u32) -> Option<String> {
+        if self.face_flags & freetype_crate::freetype::FT_FACE_FLAG_GLYPH_NAMES as c_long == 0 {
+            return None;
+        }
+
+        let mut buffer = [0 as c_char; 256];
+        unsafe {
+            if freetype_crate::freetype::FT_Get_Glyph_Name(
+                self.face,
+                index,
+                buffer.as_mut_ptr() as *mut _,
+                buffer.len() as _,
+            ) != 0
+            {
+                return None;
+            }
+            to_optional_string(buffer.as_ptr()).filter(|name| !name.is_empty())
+        }
+    }
+
+    /// Returns the index of the glyph called `name`, if the face has glyph names.
+    pub fn get_name_index(&self, name: &str) -> Option<u32> {
+        if self.face_flags & freetype_crate::freetype::FT_FACE_FLAG_GLYPH_NAMES as c_long == 0 {
+            return None;
+        }
+
+        let name = CString::new(name).ok()?;
+        match unsafe {
+            freetype_crate::freetype::FT_Get_Name_Index(self.face, name.as_ptr() as *mut _)
+        } {
+            0 => None,
+            index => Some(index),
+        }
+    }
+}
+
+#[cfg(any(feature = "freetype", feature = "dox"))]
+impl<'a> Deref for LockedFtFace<'a> {
+    type Target = freetype_crate::freetype::FT_FaceRec_;
+
+    fn deref(&self) -> &freetype_crate::freetype::FT_FaceRec_ {
+        unsafe { &*self.face }
+    }
+}
+
+#[cfg(any(feature = "freetype", feature = "dox"))]
+impl<'a> Drop for LockedFtFace<'a> {
+    fn drop(&mut self) {
+        unsafe { ffi::cairo_ft_scaled_font_unlock_face(self.scaled_font.to_raw_none()) }
+    }
+}
+
+#[cfg(any(feature = "freetype", feature = "dox"))]
+impl<'a> fmt::Debug for LockedFtFace<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LockedFtFace")
+            .field("scaled_font", self.scaled_font)
+            .field("face", &self.face)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enums::{FontSlant, FontWeight};
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    use font::FontconfigQuery;
+    use paths::PathSegment;
+
+    fn toy_scaled_font() -> ScaledFont {
+        let face = FontFace::toy_create("sans-serif", FontSlant::Normal, FontWeight::Normal);
+        let mut font_matrix = Matrix::identity();
+        font_matrix.scale(20.0, 20.0);
+        ScaledFont::new(
+            &face,
+            &font_matrix,
+            &Matrix::identity(),
+            &FontOptions::new(),
+        )
+    }
+
+    #[test]
+    fn text_outline() {
+        let scaled_font = toy_scaled_font();
+        assert!(scaled_font
+            .text_outline("", 0.0, 0.0)
+            .iter()
+            .next()
+            .is_none());
+
+        let path = scaled_font.text_outline("H", 10.0, 30.0);
+        let mut segments = path.iter();
+        match segments.next() {
+            Some(PathSegment::MoveTo((x, y))) => assert!(x >= 10.0 && y <= 30.0),
+            segment => panic!("Unexpected segment {:?}", segment),
+        }
+        assert!(segments.any(|s| s == PathSegment::ClosePath));
+    }
+
+    #[test]
+    fn glyph_outline_matches_text_outline() {
+        let scaled_font = toy_scaled_font();
+        let (glyphs, _) = scaled_font.text_to_glyphs(0.0, 0.0, "o");
+        let glyph = scaled_font.glyph_outline(glyphs[0].index as u64);
+        let text = scaled_font.text_outline("o", 0.0, 0.0);
+        assert!(glyph.iter().eq(text.iter()));
+    }
+
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    #[test]
+    fn lock_ft_face() {
+        let options = FontOptions::new();
+        let face =
+            FontFace::from_fontconfig(&FontconfigQuery::new("sans-serif"), &options).unwrap();
+        let mut font_matrix = Matrix::identity();
+        font_matrix.scale(12.0, 12.0);
+        let scaled_font = ScaledFont::new(&face, &font_matrix, &Matrix::identity(), &options);
+
+        let locked = scaled_font.lock_ft_face().unwrap();
+        assert!(!locked.as_ptr().is_null());
+        assert!(locked.num_glyphs > 0);
+        assert!(locked.has_char('a'));
+        assert!(!locked.has_char('\u{10fffd}'));
+
+        let a = locked.get_char_index('A').unwrap();
+        let v = locked.get_char_index('V').unwrap();
+        assert_eq!(locked.get_glyph_name(a).as_ref().map(|s| &s[..]), Some("A"));
+        assert_eq!(locked.get_name_index("A"), Some(a));
+        assert_eq!(locked.get_name_index("no-such-glyph"), None);
+        let (x, y) = locked.get_kerning(a, v).unwrap();
+        assert!(x <= 0.0 && y == 0.0);
+    }
+
+    #[cfg(any(feature = "freetype", feature = "dox"))]
+    #[test]
+    #[ignore = "needs an installed variable font"]
+    fn variation_axes_are_ordered() {
+        let options = FontOptions::new();
+        let axes = FontconfigQuery::list_families()
+            .unwrap()
+            .into_iter()
+            .find_map(|family| {
+                let face =
+                    FontFace::from_fontconfig(&FontconfigQuery::new(&family), &options).ok()?;
+                let scaled_font =
+                    ScaledFont::new(&face, &Matrix::identity(), &Matrix::identity(), &options);
+                let axes = scaled_font.get_variation_axes().unwrap();
+                if axes.is_empty() {
+                    None
+                } else {
+                    Some(axes)
+                }
+            })
+            .expect("No variable font installed");
+
+        for axis in axes {
+            assert!(axis.minimum <= axis.default && axis.default <= axis.maximum);
+        }
+    }
+}
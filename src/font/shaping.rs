@@ -0,0 +1,384 @@
+// Copyright 2020, The Gtk-rs Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <https://opensource.org/licenses/MIT>
+
+use ffi;
+use ffi::harfbuzz as hb;
+use ffi::{Glyph, TextCluster};
+use libc::{c_char, c_int, c_long, c_uint};
+use std::ptr;
+use std::slice;
+
+use enums::{HintMetrics, TextClusterFlags};
+use error::Error;
+
+use super::{LockedFtFace, ScaledFont};
+
+/// Settings for `ScaledFont::shape`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ShapingOptions {
+    /// OpenType feature settings in HarfBuzz syntax, e.g. `"liga=0"`, `"+smcp"` or `"ss01"`.
+    pub features: Vec<String>,
+    /// ISO 15924 script tag, e.g. `"Arab"`. Guessed from the text when unset.
+    pub script: Option<String>,
+    /// BCP 47 language tag, e.g. `"fa"`. Defaults to the locale's language when unset.
+    pub language: Option<String>,
+}
+
+// HarfBuzz objects used for one shaping call.
+struct Shaper {
+    face: *mut hb::hb_face_t,
+    font: *mut hb::hb_font_t,
+    buffer: *mut hb::hb_buffer_t,
+}
+
+impl Drop for Shaper {
+    fn drop(&mut self) {
+        unsafe {
+            hb::hb_buffer_destroy(self.buffer);
+            hb::hb_font_destroy(self.font);
+            hb::hb_face_destroy(self.face);
+        }
+    }
+}
+
+impl ScaledFont {
+    /// Shapes `text` with HarfBuzz, using the FreeType face behind this font, and positions
+    /// the result starting at (`x`, `y`).
+    ///
+    /// The font's size, variations and metrics hinting are applied, so advances agree with
+    /// `glyph_extents` for fonts whose scale matrix is not rotated or skewed.
+    ///
+    /// The glyphs, clusters and cluster flags can be passed as is to
+    /// `Context::show_text_glyphs`. Returns `Error::FontTypeMismatch` if this is not a
+    /// FreeType font.
+    pub fn shape(
+        &self,
+        x: f64,
+        y: f64,
+        text: &str,
+        options: &ShapingOptions,
+    ) -> Result<(Vec<Glyph>, Vec<TextCluster>, TextClusterFlags), Error> {
+        let features = options
+            .features
+            .iter()
+            .map(|feature| unsafe {
+                let mut parsed = hb::hb_feature_t {
+                    tag: 0,
+                    value: 0,
+                    start: 0,
+                    end: 0,
+                };
+                if hb::hb_feature_from_string(
+                    feature.as_ptr() as *const c_char,
+                    feature.len() as c_int,
+                    &mut parsed,
+                ) != 0
+                {
+                    Ok(parsed)
+                } else {
+                    Err(Error::InvalidString)
+                }
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let locked = self.lock_ft_face()?;
+        let font_matrix = self.get_font_matrix();
+        let scale_matrix = self.get_scale_matrix();
+        let hint_metrics = self.get_font_options().get_hint_metrics() != HintMetrics::Off;
+
+        // Size in device pixels along each axis of the font, in 26.6 fixed point like
+        // FreeType's. HarfBuzz positions come out in the same units.
+        let x_ppem = scale_matrix.xx.hypot(scale_matrix.yx);
+        let y_ppem = scale_matrix.xy.hypot(scale_matrix.yy);
+        let x_scale = ((x_ppem * 64.0).round() as c_int).max(1);
+        let y_scale = ((y_ppem * 64.0).round() as c_int).max(1);
+
+        unsafe {
+            let coords = blend_coordinates(&locked)?;
+            let face = hb::hb_ft_face_create_referenced(locked.as_ptr() as ffi::FT_Face);
+            let shaper = Shaper {
+                face,
+                font: hb::hb_font_create(face),
+                buffer: hb::hb_buffer_create(),
+            };
+            if hb::hb_buffer_allocation_successful(shaper.buffer) == 0 {
+                return Err(Error::NoMemory);
+            }
+
+            hb::hb_font_set_scale(shaper.font, x_scale, y_scale);
+            hb::hb_font_set_ppem(
+                shaper.font,
+                x_ppem.round() as c_uint,
+                y_ppem.round() as c_uint,
+            );
+            hb::hb_font_set_var_coords_normalized(
+                shaper.font,
+                coords.as_ptr(),
+                coords.len() as c_uint,
+            );
+
+            hb::hb_buffer_add_utf8(
+                shaper.buffer,
+                text.as_ptr() as *const c_char,
+                text.len() as c_int,
+                0,
+                text.len() as c_int,
+            );
+            if let Some(ref script) = options.script {
+                let script = hb::hb_script_from_string(
+                    script.as_ptr() as *const c_char,
+                    script.len() as c_int,
+                );
+                if script == hb::HB_SCRIPT_INVALID || script == hb::HB_SCRIPT_UNKNOWN {
+                    return Err(Error::InvalidString);
+                }
+                hb::hb_buffer_set_script(shaper.buffer, script);
+            }
+            if let Some(ref language) = options.language {
+                let language = hb::hb_language_from_string(
+                    language.as_ptr() as *const c_char,
+                    language.len() as c_int,
+                );
+                if language.is_null() {
+                    return Err(Error::InvalidString);
+                }
+                hb::hb_buffer_set_language(shaper.buffer, language);
+            }
+            hb::hb_buffer_guess_segment_properties(shaper.buffer);
+
+            hb::hb_shape(
+                shaper.font,
+                shaper.buffer,
+                features.as_ptr(),
+                features.len() as _,
+            );
+            if hb::hb_buffer_allocation_successful(shaper.buffer) == 0 {
+                return Err(Error::NoMemory);
+            }
+
+            let mut length = 0;
+            let infos = hb::hb_buffer_get_glyph_infos(shaper.buffer, &mut length);
+            let positions = hb::hb_buffer_get_glyph_positions(shaper.buffer, ptr::null_mut());
+            let (infos, positions) = if length == 0 {
+                (&[][..], &[][..])
+            } else {
+                (
+                    slice::from_raw_parts(infos, length as usize),
+                    slice::from_raw_parts(positions, length as usize),
+                )
+            };
+
+            // Back to ems, then through the font matrix, so that positions end up in user
+            // space like the ones from `text_to_glyphs`.
+            let to_user = |dx: i32, dy: i32| {
+                // HarfBuzz's y axis points up, cairo's points down.
+                font_matrix.transform_distance(
+                    f64::from(dx) / f64::from(x_scale),
+                    -f64::from(dy) / f64::from(y_scale),
+                )
+            };
+            // cairo rounds advances to whole device pixels when hinting metrics.
+            let advance = |distance: i32| {
+                if hint_metrics {
+                    (distance + 32) & !63
+                } else {
+                    distance
+                }
+            };
+            let (mut pen_x, mut pen_y) = (0, 0);
+            let glyphs = infos
+                .iter()
+                .zip(positions)
+                .map(|(info, position)| {
+                    let (dx, dy) = to_user(pen_x + position.x_offset, pen_y + position.y_offset);
+                    pen_x += advance(position.x_advance);
+                    pen_y += advance(position.y_advance);
+                    Glyph {
+                        index: info.codepoint as _,
+                        x: x + dx,
+                        y: y + dy,
+                    }
+                })
+                .collect();
+
+            let direction = hb::hb_buffer_get_direction(shaper.buffer);
+            let backward = direction == hb::HB_DIRECTION_RTL || direction == hb::HB_DIRECTION_BTT;
+            let clusters = clusters_from_offsets(
+                infos.iter().map(|info| info.cluster as usize),
+                text.len(),
+                backward,
+            );
+            let flags = if backward {
+                TextClusterFlags::Backward
+            } else {
+                TextClusterFlags::None
+            };
+
+            Ok((glyphs, clusters, flags))
+        }
+    }
+}
+
+// Normalized variation coordinates of the instance cairo selected on `face`, in HarfBuzz's
+// 2.14 format instead of FreeType's 16.16.
+unsafe fn blend_coordinates(face: &LockedFtFace) -> Result<Vec<c_int>, Error> {
+    if face.face_flags & freetype_crate::freetype::FT_FACE_FLAG_MULTIPLE_MASTERS as c_long == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut mm_var: *mut ffi::FT_MM_Var = ptr::null_mut();
+    if ffi::FT_Get_MM_Var(face.as_ptr() as ffi::FT_Face, &mut mm_var) != 0 {
+        return Err(Error::FreetypeError);
+    }
+    let num_axis = (*mm_var).num_axis;
+    ffi::FT_Done_MM_Var((*face.glyph).library as ffi::FT_Library, mm_var);
+
+    let mut coords: Vec<c_long> = vec![0; num_axis as usize];
+    if ffi::FT_Get_Var_Blend_Coordinates(
+        face.as_ptr() as ffi::FT_Face,
+        num_axis,
+        coords.as_mut_ptr(),
+    ) != 0
+    {
+        return Err(Error::FreetypeError);
+    }
+    Ok(coords
+        .into_iter()
+        .map(|coord| (coord >> 2) as c_int)
+        .collect())
+}
+
+// Builds cairo clusters from the byte offset of the cluster each glyph belongs to, in glyph
+// order. The offsets are increasing, or decreasing if `backward`.
+pub(crate) fn clusters_from_offsets<I: Iterator<Item = usize>>(
+    offsets: I,
+    text_len: usize,
+    backward: bool,
+) -> Vec<TextCluster> {
+    let mut runs: Vec<(usize, c_int)> = Vec::new();
+    for offset in offsets {
+        match runs.last_mut() {
+            Some(&mut (start, ref mut num_glyphs)) if start == offset => *num_glyphs += 1,
+            _ => runs.push((offset, 1)),
+        }
+    }
+
+    let mut starts: Vec<usize> = runs.iter().map(|&(start, _)| start).collect();
+    if backward {
+        starts.reverse();
+    }
+    runs.iter()
+        .map(|&(start, num_glyphs)| {
+            let index = starts.binary_search(&start).unwrap_or_else(|i| i);
+            let end = starts.get(index + 1).cloned().unwrap_or(text_len);
+            TextCluster {
+                num_bytes: (end - start) as c_int,
+                num_glyphs,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enums::HintStyle;
+    use font::{FontFace, FontOptions, FontconfigQuery};
+    use matrices::Matrix;
+
+    fn scaled_font() -> ScaledFont {
+        scaled_font_with_options(&FontOptions::new())
+    }
+
+    fn scaled_font_with_options(options: &FontOptions) -> ScaledFont {
+        let face = FontFace::from_fontconfig(&FontconfigQuery::new("sans-serif"), options).unwrap();
+        let mut font_matrix = Matrix::identity();
+        font_matrix.scale(10.0, 10.0);
+        ScaledFont::new(&face, &font_matrix, &Matrix::identity(), options)
+    }
+
+    fn total_bytes(clusters: &[TextCluster]) -> c_int {
+        clusters.iter().map(|c| c.num_bytes).sum()
+    }
+
+    #[test]
+    fn clusters_cover_text() {
+        let clusters = clusters_from_offsets(vec![0, 1, 1, 3].into_iter(), 5, false);
+        let sizes: Vec<_> = clusters
+            .iter()
+            .map(|c| (c.num_bytes, c.num_glyphs))
+            .collect();
+        assert_eq!(sizes, [(1, 1), (2, 2), (2, 1)]);
+
+        let clusters = clusters_from_offsets(vec![3, 1, 1, 0].into_iter(), 5, true);
+        let sizes: Vec<_> = clusters
+            .iter()
+            .map(|c| (c.num_bytes, c.num_glyphs))
+            .collect();
+        assert_eq!(sizes, [(2, 1), (2, 2), (1, 1)]);
+    }
+
+    #[test]
+    fn shape_latin() {
+        let text = "Hello";
+        let (glyphs, clusters, flags) = scaled_font()
+            .shape(5.0, 20.0, text, &ShapingOptions::default())
+            .unwrap();
+        assert_eq!(glyphs.len(), 5);
+        assert_eq!(flags, TextClusterFlags::None);
+        assert_eq!(total_bytes(&clusters), text.len() as c_int);
+        assert_eq!((glyphs[0].x, glyphs[0].y), (5.0, 20.0));
+        assert!(glyphs.windows(2).all(|w| w[1].x > w[0].x));
+    }
+
+    #[test]
+    fn advances_match_glyph_extents() {
+        for &hint_metrics in &[HintMetrics::Off, HintMetrics::On] {
+            let mut options = FontOptions::new();
+            options.set_hint_style(HintStyle::None);
+            options.set_hint_metrics(hint_metrics);
+            let font = scaled_font_with_options(&options);
+
+            // No ligatures or kerning, so each advance is the glyph's own.
+            let shaping = ShapingOptions {
+                features: vec!["-liga".to_owned(), "-kern".to_owned()],
+                ..Default::default()
+            };
+            let (glyphs, _, _) = font.shape(0.0, 0.0, "Wave.", &shaping).unwrap();
+            for pair in glyphs.windows(2) {
+                let extents = font.glyph_extents(&pair[..1]);
+                let shaped = pair[1].x - pair[0].x;
+                assert!(
+                    (shaped - extents.x_advance).abs() < 0.02,
+                    "{:?}: shaped advance {} != {}",
+                    hint_metrics,
+                    shaped,
+                    extents.x_advance
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn shape_rtl_is_backward() {
+        let text = "\u{5e9}\u{5dc}\u{5d5}\u{5dd}";
+        let (_, clusters, flags) = scaled_font()
+            .shape(0.0, 0.0, text, &ShapingOptions::default())
+            .unwrap();
+        assert_eq!(flags, TextClusterFlags::Backward);
+        assert_eq!(total_bytes(&clusters), text.len() as c_int);
+    }
+
+    #[test]
+    fn invalid_feature() {
+        let options = ShapingOptions {
+            features: vec!["=".to_owned()],
+            ..Default::default()
+        };
+        assert_eq!(
+            scaled_font().shape(0.0, 0.0, "a", &options).unwrap_err(),
+            Error::InvalidString
+        );
+    }
+}
@@ -20,6 +20,10 @@
 //! * **svg** - Rendering SVG documents
 //! * **ps** - Rendering PostScript documents
 //!
+//! ### Text features
+//!
+//! * **harfbuzz** - Shaping text with HarfBuzz
+//!
 //! ### Cairo API version features
 //!
 //! * **v1_14** - Use Cairo 1.14 APIs
@@ -36,6 +40,10 @@
 //! * **xcb** - X Window System rendering using the XCB library
 //! * **xlib** - X Window System rendering using XLib
 //!
+//! ### Serialization features
+//!
+//! * **serde** - Serialize geometry, enums, glyphs, paths and gradients with serde
+//!
 //! ### Windows API features
 //!
 //! * **win32-surface** - Microsoft Windows surface support
@@ -43,6 +51,8 @@
 extern crate cairo_sys as ffi;
 extern crate libc;
 extern crate thiserror;
+extern crate unicode_bidi;
+extern crate unicode_linebreak;
 
 #[macro_use]
 extern crate bitflags;
@@ -57,9 +67,16 @@ extern crate glib_sys as glib_ffi;
 #[cfg(feature = "use_glib")]
 extern crate gobject_sys as gobject_ffi;
 
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde_crate;
+
 #[cfg(test)]
 extern crate tempfile;
 
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+
 // Helper macro for our GValue related trait impls
 #[cfg(feature = "use_glib")]
 macro_rules! gvalue_impl {
@@ -110,6 +127,10 @@ macro_rules! gvalue_impl {
 
 pub use user_data::UserDataKey;
 
+pub use color::Rgba;
+
+pub use conic_gradient::ConicGradient;
+
 pub use context::{Context, RectangleList};
 
 pub use paths::{Path, PathSegment, PathSegments};
@@ -120,24 +141,36 @@ pub use enums::*;
 
 pub use error::{BorrowError, Error, IoError};
 
+pub use fill_patterns::FillPatternOptions;
+
 pub use patterns::{
-    Gradient, LinearGradient, Mesh, Pattern, RadialGradient, SolidPattern, SurfacePattern,
+    Gradient, LinearGradient, Mesh, Pattern, PatternKind, RadialGradient, SolidPattern,
+    SurfacePattern,
 };
 
 pub use font::{
-    FontExtents, FontFace, FontOptions, FontSlant, FontType, FontWeight, Glyph, ScaledFont,
-    TextCluster, TextExtents,
+    AtlasGlyph, AttributedString, BidiRun, FontExtents, FontFace, FontOptions, FontSlant, FontType,
+    FontVariationAxis, FontVariations, FontWeight, Glyph, GlyphAtlas, LayoutLine, LayoutOptions,
+    LineExtents, ParagraphLayout, ScaledFont, ShelfPacker, StyledRun, TextAlignment, TextCluster,
+    TextDecoration, TextDecorationMetrics, TextDirection, TextExtents, TextStyle,
 };
 
+#[cfg(any(feature = "harfbuzz", feature = "dox"))]
+pub use font::ShapingOptions;
+#[cfg(any(feature = "freetype", feature = "dox"))]
+pub use font::{FallbackRun, FontFallbackChain, FontInfo, FontconfigQuery, LockedFtFace};
+
 pub use matrices::Matrix;
 
+pub use mesh_builder::{MeshBuilder, MeshVertex};
+
 pub use recording_surface::RecordingSurface;
 pub use rectangle::Rectangle;
 pub use rectangle_int::RectangleInt;
 
-pub use region::Region;
+pub use region::{Region, RegionRectangles};
 
-pub use surface::{MappedImageSurface, Surface};
+pub use surface::{MappedImageSurface, Surface, SurfaceDowncast, SurfaceKind};
 
 pub use image_surface::{ImageSurface, ImageSurfaceData};
 
@@ -167,15 +200,19 @@ mod constants;
 pub use constants::*;
 mod utils;
 pub use utils::{debug_reset_static_data, get_version_string, Version};
+mod color;
+mod conic_gradient;
 mod context;
 mod device;
 mod enums;
 mod error;
+mod fill_patterns;
 mod font;
 mod image_surface;
 #[cfg(any(feature = "png", feature = "dox"))]
 mod image_surface_png;
 mod matrices;
+mod mesh_builder;
 mod paths;
 mod patterns;
 mod recording_surface;
@@ -9,6 +9,11 @@ use utils::status_to_result;
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub struct Matrix {
     pub xx: c_double,
     pub yx: c_double,
@@ -0,0 +1,342 @@
+// Copyright 2020, The Gtk-rs Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <https://opensource.org/licenses/MIT>
+
+use enums::MeshCorner;
+use error::Error;
+use patterns::Mesh;
+use Rgba;
+
+/// A point of a mesh with its color.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeshVertex {
+    pub x: f64,
+    pub y: f64,
+    pub color: Rgba,
+}
+
+impl MeshVertex {
+    pub fn new(x: f64, y: f64, color: Rgba) -> MeshVertex {
+        MeshVertex { x, y, color }
+    }
+}
+
+/// Builds a `Mesh`, checking that patches are defined in the order cairo expects.
+///
+/// Calls out of order return `Error::InvalidMeshConstruction` and leave the mesh unchanged,
+/// instead of putting the pattern in an error state.
+#[derive(Debug, Default)]
+pub struct MeshBuilder {
+    mesh: Mesh,
+    patch: Option<Patch>,
+}
+
+// State of the patch being defined.
+#[derive(Clone, Copy, Debug)]
+struct Patch {
+    started: bool,
+    sides: usize,
+}
+
+impl MeshBuilder {
+    pub fn new() -> MeshBuilder {
+        MeshBuilder::default()
+    }
+
+    /// Starts a patch. Fails if a patch is already started.
+    pub fn begin_patch(&mut self) -> Result<(), Error> {
+        if self.patch.is_some() {
+            return Err(Error::InvalidMeshConstruction);
+        }
+        self.mesh.begin_patch();
+        self.patch = Some(Patch {
+            started: false,
+            sides: 0,
+        });
+        Ok(())
+    }
+
+    /// Sets the first point of the patch. Fails if it is already set.
+    pub fn move_to(&mut self, x: f64, y: f64) -> Result<(), Error> {
+        match self.patch {
+            Some(ref mut patch) if !patch.started => patch.started = true,
+            _ => return Err(Error::InvalidMeshConstruction),
+        }
+        self.mesh.move_to(x, y);
+        Ok(())
+    }
+
+    /// Adds a straight side to the patch. Fails before `move_to` or after four sides.
+    pub fn line_to(&mut self, x: f64, y: f64) -> Result<(), Error> {
+        self.add_side()?;
+        self.mesh.line_to(x, y);
+        Ok(())
+    }
+
+    /// Adds a curved side to the patch. Fails before `move_to` or after four sides.
+    pub fn curve_to(
+        &mut self,
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+        x3: f64,
+        y3: f64,
+    ) -> Result<(), Error> {
+        self.add_side()?;
+        self.mesh.curve_to(x1, y1, x2, y2, x3, y3);
+        Ok(())
+    }
+
+    /// Sets the inner control point of the patch next to `corner`. Fails outside of a patch.
+    pub fn set_control_point(&mut self, corner: MeshCorner, x: f64, y: f64) -> Result<(), Error> {
+        self.current_patch()?;
+        self.mesh.set_control_point(corner, x, y);
+        Ok(())
+    }
+
+    /// Sets the color of `corner`. Fails outside of a patch.
+    ///
+    /// Corners without a color are transparent, except the ones `end_patch` adds, which get
+    /// the color of the first corner.
+    pub fn set_corner_color(&mut self, corner: MeshCorner, color: Rgba) -> Result<(), Error> {
+        self.current_patch()?;
+        self.mesh.set_corner_color(corner, color);
+        Ok(())
+    }
+
+    /// Ends the patch, closing it with straight sides back to its first point if it has less
+    /// than four sides. Fails before `move_to`.
+    pub fn end_patch(&mut self) -> Result<(), Error> {
+        if !self.current_patch()?.started {
+            return Err(Error::InvalidMeshConstruction);
+        }
+        self.mesh.end_patch();
+        self.patch = None;
+        Ok(())
+    }
+
+    /// Adds a triangle as a patch with a degenerate fourth side.
+    pub fn add_triangle(
+        &mut self,
+        a: &MeshVertex,
+        b: &MeshVertex,
+        c: &MeshVertex,
+    ) -> Result<(), Error> {
+        self.begin_patch()?;
+        self.move_to(a.x, a.y)?;
+        self.line_to(b.x, b.y)?;
+        self.line_to(c.x, c.y)?;
+        self.set_corner_color(MeshCorner::MeshCorner0, a.color)?;
+        self.set_corner_color(MeshCorner::MeshCorner1, b.color)?;
+        self.set_corner_color(MeshCorner::MeshCorner2, c.color)?;
+        self.set_corner_color(MeshCorner::MeshCorner3, a.color)?;
+        self.end_patch()
+    }
+
+    /// Adds a triangle for each triple of indices into `vertices`.
+    ///
+    /// Returns `Error::InvalidIndex`, without adding any triangle, if an index is out of
+    /// bounds.
+    pub fn add_triangles(
+        &mut self,
+        vertices: &[MeshVertex],
+        triangles: &[[usize; 3]],
+    ) -> Result<(), Error> {
+        if triangles.iter().flatten().any(|&i| i >= vertices.len()) {
+            return Err(Error::InvalidIndex);
+        }
+        for &[a, b, c] in triangles {
+            self.add_triangle(&vertices[a], &vertices[b], &vertices[c])?;
+        }
+        Ok(())
+    }
+
+    /// Adds a patch for each cell of a grid of `vertices`, given row by row with `columns`
+    /// vertices per row.
+    ///
+    /// Returns `Error::InvalidSize` unless the grid has at least two rows and two columns.
+    pub fn add_grid(&mut self, columns: usize, vertices: &[MeshVertex]) -> Result<(), Error> {
+        if columns < 2 || vertices.len() < 2 * columns || vertices.len() % columns != 0 {
+            return Err(Error::InvalidSize);
+        }
+        let rows = vertices.len() / columns;
+        for row in 0..rows - 1 {
+            for column in 0..columns - 1 {
+                let corners = [
+                    &vertices[row * columns + column],
+                    &vertices[row * columns + column + 1],
+                    &vertices[(row + 1) * columns + column + 1],
+                    &vertices[(row + 1) * columns + column],
+                ];
+                self.add_quad(&corners)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the mesh. Fails if a patch is not ended.
+    pub fn build(self) -> Result<Mesh, Error> {
+        if self.patch.is_some() {
+            return Err(Error::InvalidMeshConstruction);
+        }
+        self.mesh.status()?;
+        Ok(self.mesh)
+    }
+
+    fn add_quad(&mut self, corners: &[&MeshVertex; 4]) -> Result<(), Error> {
+        let colors = [
+            MeshCorner::MeshCorner0,
+            MeshCorner::MeshCorner1,
+            MeshCorner::MeshCorner2,
+            MeshCorner::MeshCorner3,
+        ];
+        self.begin_patch()?;
+        self.move_to(corners[0].x, corners[0].y)?;
+        for vertex in &corners[1..] {
+            self.line_to(vertex.x, vertex.y)?;
+        }
+        for (&corner, vertex) in colors.iter().zip(corners) {
+            self.set_corner_color(corner, vertex.color)?;
+        }
+        self.end_patch()
+    }
+
+    fn current_patch(&self) -> Result<Patch, Error> {
+        self.patch.ok_or(Error::InvalidMeshConstruction)
+    }
+
+    fn add_side(&mut self) -> Result<(), Error> {
+        match self.patch {
+            Some(ref mut patch) if patch.started && patch.sides < 4 => {
+                patch.sides += 1;
+                Ok(())
+            }
+            _ => Err(Error::InvalidMeshConstruction),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paths::PathSegment;
+
+    // Returns the first point of a patch and the end points of its sides.
+    fn corners(mesh: &Mesh, patch: usize) -> Vec<(f64, f64)> {
+        mesh.get_path(patch)
+            .iter()
+            .filter_map(|segment| match segment {
+                PathSegment::MoveTo(p) | PathSegment::LineTo(p) | PathSegment::CurveTo(_, _, p) => {
+                    Some(p)
+                }
+                PathSegment::ClosePath => None,
+            })
+            .collect()
+    }
+
+    fn vertex(x: f64, y: f64) -> MeshVertex {
+        MeshVertex::new(x, y, Rgba::rgb(x / 10.0, y / 10.0, 0.0))
+    }
+
+    #[test]
+    fn sequencing_errors() {
+        let mut builder = MeshBuilder::new();
+        assert_eq!(
+            builder.move_to(0.0, 0.0),
+            Err(Error::InvalidMeshConstruction)
+        );
+        assert_eq!(builder.end_patch(), Err(Error::InvalidMeshConstruction));
+        assert_eq!(
+            builder.set_corner_color(MeshCorner::MeshCorner0, Rgba::rgb(1.0, 0.0, 0.0)),
+            Err(Error::InvalidMeshConstruction)
+        );
+
+        builder.begin_patch().unwrap();
+        assert_eq!(builder.begin_patch(), Err(Error::InvalidMeshConstruction));
+        assert_eq!(
+            builder.line_to(1.0, 0.0),
+            Err(Error::InvalidMeshConstruction)
+        );
+        assert_eq!(builder.end_patch(), Err(Error::InvalidMeshConstruction));
+        builder.move_to(0.0, 0.0).unwrap();
+        assert_eq!(
+            builder.move_to(0.0, 0.0),
+            Err(Error::InvalidMeshConstruction)
+        );
+        for &(x, y) in &[(1.0, 0.0), (1.0, 1.0), (0.0, 1.0), (0.0, 0.0)] {
+            builder.line_to(x, y).unwrap();
+        }
+        assert_eq!(
+            builder.line_to(2.0, 2.0),
+            Err(Error::InvalidMeshConstruction)
+        );
+
+        let mut open = MeshBuilder::new();
+        open.begin_patch().unwrap();
+        assert_eq!(open.build().unwrap_err(), Error::InvalidMeshConstruction);
+
+        builder.end_patch().unwrap();
+        assert_eq!(builder.build().unwrap().get_patch_count(), 1);
+    }
+
+    #[test]
+    fn triangles() {
+        let vertices = [vertex(0.0, 0.0), vertex(10.0, 0.0), vertex(0.0, 10.0)];
+        let mut builder = MeshBuilder::new();
+        assert_eq!(
+            builder.add_triangles(&vertices, &[[0, 1, 2], [0, 1, 3]]),
+            Err(Error::InvalidIndex)
+        );
+        builder
+            .add_triangles(&vertices, &[[0, 1, 2], [2, 1, 0]])
+            .unwrap();
+        let mesh = builder.build().unwrap();
+
+        assert_eq!(mesh.get_patch_count(), 2);
+        assert_eq!(
+            corners(&mesh, 1),
+            [
+                (0.0, 10.0),
+                (10.0, 0.0),
+                (0.0, 0.0),
+                (0.0, 10.0),
+                (0.0, 10.0)
+            ]
+        );
+        assert_eq!(
+            mesh.get_corner_color_rgba(0, MeshCorner::MeshCorner3),
+            vertices[0].color.into()
+        );
+    }
+
+    #[test]
+    fn grid() {
+        let mut builder = MeshBuilder::new();
+        assert_eq!(
+            builder.add_grid(1, &[vertex(0.0, 0.0)]),
+            Err(Error::InvalidSize)
+        );
+        assert_eq!(
+            builder.add_grid(2, &[vertex(0.0, 0.0), vertex(1.0, 0.0), vertex(0.0, 1.0)]),
+            Err(Error::InvalidSize)
+        );
+
+        let vertices: Vec<MeshVertex> = (0..3)
+            .flat_map(|y| (0..4).map(move |x| vertex(f64::from(x), f64::from(y))))
+            .collect();
+        builder.add_grid(4, &vertices).unwrap();
+        let mesh = builder.build().unwrap();
+
+        assert_eq!(mesh.get_patch_count(), 6);
+        // The last cell, from (2, 1) to (3, 2).
+        assert_eq!(
+            corners(&mesh, 5),
+            [(2.0, 1.0), (3.0, 1.0), (3.0, 2.0), (2.0, 2.0), (2.0, 1.0)]
+        );
+        assert_eq!(
+            mesh.get_corner_color_rgba(5, MeshCorner::MeshCorner3),
+            vertex(2.0, 2.0).color.into()
+        );
+    }
+}
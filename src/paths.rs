@@ -2,6 +2,7 @@
 // See the COPYRIGHT file at the top-level directory of this distribution.
 // Licensed under the MIT license, see the LICENSE file or <https://opensource.org/licenses/MIT>
 
+use context::Context;
 use enums::PathDataType;
 use ffi;
 use ffi::cairo_path_t;
@@ -9,6 +10,9 @@ use std::fmt;
 use std::iter::Iterator;
 use std::ptr;
 
+#[cfg(feature = "serde")]
+use serde_crate::{Deserialize, Deserializer, Serialize, Serializer};
+
 #[derive(Debug)]
 pub struct Path(ptr::NonNull<cairo_path_t>);
 
@@ -22,6 +26,22 @@ impl Path {
         Path(ptr::NonNull::new_unchecked(pointer))
     }
 
+    /// Creates a path from segments, as if drawn on a `Context` with an identity matrix.
+    pub fn from_segments<I: IntoIterator<Item = PathSegment>>(segments: I) -> Path {
+        let cr = Context::scratch();
+        for segment in segments {
+            match segment {
+                PathSegment::MoveTo((x, y)) => cr.move_to(x, y),
+                PathSegment::LineTo((x, y)) => cr.line_to(x, y),
+                PathSegment::CurveTo((x1, y1), (x2, y2), (x3, y3)) => {
+                    cr.curve_to(x1, y1, x2, y2, x3, y3)
+                }
+                PathSegment::ClosePath => cr.close_path(),
+            }
+        }
+        cr.copy_path()
+    }
+
     pub fn iter(&self) -> PathSegments {
         use std::slice;
 
@@ -58,7 +78,27 @@ impl fmt::Display for Path {
     }
 }
 
+// Paths are serialized as their list of segments.
+#[cfg(feature = "serde")]
+impl Serialize for Path {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Path {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Path, D::Error> {
+        Vec::<PathSegment>::deserialize(deserializer).map(Path::from_segments)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum PathSegment {
     MoveTo((f64, f64)),
     LineTo((f64, f64)),
@@ -125,6 +165,67 @@ fn to_tuple(pair: &[f64; 2]) -> (f64, f64) {
     (pair[0], pair[1])
 }
 
+// Distances along a path, for placing things at a given distance from its start.
+//
+// Curves are measured as straight lines to their end point, so paths should be flattened
+// first, e.g. with `Context::copy_path_flat`. Subpaths follow each other without gaps.
+#[derive(Debug)]
+pub(crate) struct PathMeasure {
+    // (start, end, distance from the path start to `start`)
+    lines: Vec<((f64, f64), (f64, f64), f64)>,
+    length: f64,
+}
+
+impl PathMeasure {
+    pub(crate) fn new(path: &Path) -> PathMeasure {
+        let mut lines = Vec::new();
+        let mut length = 0.0;
+        let mut current = (0.0, 0.0);
+        let mut subpath_start = (0.0, 0.0);
+
+        for segment in path.iter() {
+            let end = match segment {
+                PathSegment::MoveTo(point) => {
+                    current = point;
+                    subpath_start = point;
+                    continue;
+                }
+                PathSegment::LineTo(end) | PathSegment::CurveTo(_, _, end) => end,
+                PathSegment::ClosePath => subpath_start,
+            };
+            let line_length = (end.0 - current.0).hypot(end.1 - current.1);
+            if line_length > 0.0 {
+                lines.push((current, end, length));
+                length += line_length;
+            }
+            current = end;
+        }
+
+        PathMeasure { lines, length }
+    }
+
+    pub(crate) fn length(&self) -> f64 {
+        self.length
+    }
+
+    // Returns the point at `distance` from the start of the path and the angle of the path's
+    // direction there, or `None` if `distance` is outside of the path.
+    pub(crate) fn point_at(&self, distance: f64) -> Option<(f64, f64, f64)> {
+        if distance < 0.0 || distance > self.length {
+            return None;
+        }
+
+        let index = self
+            .lines
+            .iter()
+            .rposition(|&(_, _, start)| start <= distance)?;
+        let (start, end, offset) = self.lines[index];
+        let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+        let t = (distance - offset) / dx.hypot(dy);
+        Some((start.0 + t * dx, start.1 + t * dy, dy.atan2(dx)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -167,6 +268,37 @@ mod tests {
         assert!(path.iter().next().is_none());
     }
 
+    #[test]
+    fn from_segments() {
+        let cr = make_cr();
+        cr.move_to(1.0, 2.0);
+        cr.curve_to(3.0, 4.0, 5.0, 6.0, 7.0, 8.0);
+        cr.close_path();
+        cr.line_to(9.0, 10.0);
+        let path = cr.copy_path();
+
+        let segments: Vec<PathSegment> = path.iter().collect();
+        assert_path_equals_segments(&Path::from_segments(segments.clone()), &segments);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let path = Path::from_segments(vec![
+            PathSegment::MoveTo((1.0, 2.0)),
+            PathSegment::LineTo((3.0, 4.0)),
+            PathSegment::ClosePath,
+        ]);
+        let json = ::serde_json::to_string(&path).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"MoveTo":[1.0,2.0]},{"LineTo":[3.0,4.0]},"ClosePath",{"MoveTo":[1.0,2.0]}]"#
+        );
+        let segments: Vec<PathSegment> = path.iter().collect();
+        let path: Path = ::serde_json::from_str(&json).unwrap();
+        assert_path_equals_segments(&path, &segments);
+    }
+
     #[test]
     fn moveto() {
         let cr = make_cr();
@@ -241,4 +373,30 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn measure_lines() {
+        let cr = make_cr();
+
+        cr.move_to(0.0, 0.0);
+        cr.line_to(3.0, 4.0);
+        cr.move_to(10.0, 0.0);
+        cr.line_to(10.0, 10.0);
+        cr.close_path();
+
+        let measure = PathMeasure::new(&cr.copy_path());
+
+        assert_eq!(measure.length(), 25.0);
+        assert_eq!(measure.point_at(2.5), Some((1.5, 2.0, 4f64.atan2(3.0))));
+        assert_eq!(
+            measure.point_at(7.0),
+            Some((10.0, 2.0, std::f64::consts::FRAC_PI_2))
+        );
+        assert_eq!(
+            measure.point_at(20.0),
+            Some((10.0, 5.0, -std::f64::consts::FRAC_PI_2))
+        );
+        assert_eq!(measure.point_at(-1.0), None);
+        assert_eq!(measure.point_at(25.5), None);
+    }
 }
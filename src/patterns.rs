@@ -13,10 +13,14 @@ use std::fmt;
 use std::ops::Deref;
 use std::ptr;
 use utils::status_to_result;
-use {Matrix, Path, Surface};
+use {Matrix, Path, Rectangle, Rgba, Surface};
+
+#[cfg(feature = "serde")]
+use serde_crate::de::Error as _;
+#[cfg(feature = "serde")]
+use serde_crate::{Deserialize, Deserializer, Serialize, Serializer};
 
 // See https://cairographics.org/manual/bindings-patterns.html for more info
-#[derive(Debug)]
 pub struct Pattern {
     pointer: *mut cairo_pattern_t,
 }
@@ -82,10 +86,67 @@ impl Pattern {
         matrix
     }
 
-    fn status(&self) -> Result<(), Error> {
+    pub(crate) fn status(&self) -> Result<(), Error> {
         let status = unsafe { ffi::cairo_pattern_status(self.pointer) };
         status_to_result(status)
     }
+
+    /// Returns `self` borrowed as its specific type, to `match` on.
+    pub fn kind(&self) -> PatternKind {
+        // Pattern types are `#[repr(transparent)]` wrappers of `Pattern`.
+        unsafe {
+            match self.get_type() {
+                PatternType::Solid => PatternKind::Solid(&*(self as *const _ as *const _)),
+                PatternType::Surface => PatternKind::Surface(&*(self as *const _ as *const _)),
+                PatternType::LinearGradient => {
+                    PatternKind::Linear(&*(self as *const _ as *const _))
+                }
+                PatternType::RadialGradient => {
+                    PatternKind::Radial(&*(self as *const _ as *const _))
+                }
+                PatternType::Mesh => PatternKind::Mesh(&*(self as *const _ as *const _)),
+                PatternType::RasterSource => PatternKind::RasterSource(self),
+                _ => PatternKind::Other(self),
+            }
+        }
+    }
+
+    // Adds the fields shared by all pattern types and finishes `debug`.
+    fn finish_debug(&self, debug: &mut fmt::DebugStruct) -> fmt::Result {
+        debug
+            .field("extend", &self.get_extend())
+            .field("filter", &self.get_filter())
+            .field("matrix", &self.get_matrix())
+            .finish()
+    }
+}
+
+/// A `Pattern` borrowed as its specific type, returned by `Pattern::kind`.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum PatternKind<'a> {
+    Solid(&'a SolidPattern),
+    Surface(&'a SurfacePattern),
+    Linear(&'a LinearGradient),
+    Radial(&'a RadialGradient),
+    Mesh(&'a Mesh),
+    /// Raster source patterns have no specific type in this crate.
+    RasterSource(&'a Pattern),
+    /// A pattern of a type unknown to this crate.
+    Other(&'a Pattern),
+}
+
+impl fmt::Debug for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind() {
+            PatternKind::Solid(pattern) => pattern.fmt(f),
+            PatternKind::Surface(pattern) => pattern.fmt(f),
+            PatternKind::Linear(pattern) => pattern.fmt(f),
+            PatternKind::Radial(pattern) => pattern.fmt(f),
+            PatternKind::Mesh(pattern) => pattern.fmt(f),
+            _ => self.finish_debug(f.debug_struct("Pattern").field("type", &self.get_type())),
+        }
+    }
 }
 
 impl Clone for Pattern {
@@ -126,7 +187,8 @@ macro_rules! pattern_type(
     //Signals without arguments
     ($pattern_type:ident $( = $variant: ident)*) => (
 
-        #[derive(Debug, Clone)]
+        #[derive(Clone)]
+        #[repr(transparent)]
         pub struct $pattern_type(Pattern);
 
         impl Deref for $pattern_type {
@@ -168,6 +230,10 @@ impl SolidPattern {
         }
     }
 
+    pub fn from_color(color: Rgba) -> SolidPattern {
+        SolidPattern::from_rgba(color.red, color.green, color.blue, color.alpha)
+    }
+
     pub fn get_rgba(&self) -> (f64, f64, f64, f64) {
         unsafe {
             let mut red = 0.0;
@@ -187,11 +253,30 @@ impl SolidPattern {
             (red, green, blue, alpha)
         }
     }
+
+    pub fn get_color(&self) -> Rgba {
+        self.get_rgba().into()
+    }
+}
+
+impl fmt::Debug for SolidPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.finish_debug(
+            f.debug_struct("SolidPattern")
+                .field("color", &self.get_color()),
+        )
+    }
 }
 
 pattern_type!(Gradient);
 convert!(Pattern => Gradient = LinearGradient | RadialGradient);
 
+impl fmt::Debug for Gradient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
 impl Gradient {
     pub fn add_color_stop_rgb(&self, offset: f64, red: f64, green: f64, blue: f64) {
         unsafe { ffi::cairo_pattern_add_color_stop_rgb(self.pointer, offset, red, green, blue) }
@@ -203,6 +288,10 @@ impl Gradient {
         }
     }
 
+    pub fn add_color_stop(&self, offset: f64, color: Rgba) {
+        self.add_color_stop_rgba(offset, color.red, color.green, color.blue, color.alpha)
+    }
+
     pub fn get_color_stop_count(&self) -> isize {
         unsafe {
             let mut count = 0;
@@ -234,11 +323,138 @@ impl Gradient {
             (offset, red, green, blue, alpha)
         }
     }
+
+    /// Returns the offset and color of the stop at `index`.
+    pub fn get_color_stop(&self, index: isize) -> (f64, Rgba) {
+        let (offset, red, green, blue, alpha) = self.get_color_stop_rgba(index);
+        (offset, Rgba::new(red, green, blue, alpha))
+    }
+
+    pub fn add_color_stops(&self, stops: &[(f64, Rgba)]) {
+        for &(offset, color) in stops {
+            self.add_color_stop(offset, color);
+        }
+    }
+
+    /// Returns the offsets and colors of the stops, sorted by offset.
+    pub fn get_color_stops(&self) -> Vec<(f64, Rgba)> {
+        (0..self.get_color_stop_count())
+            .map(|index| self.get_color_stop(index))
+            .collect()
+    }
+
+    /// Returns the color at `offset` along the gradient, interpolated like cairo's image
+    /// backend does, depending on the extend mode.
+    ///
+    /// With `Extend::None`, offsets before the first stop and after the last one are
+    /// transparent. With `Extend::Repeat`, colors are interpolated between the last stop and
+    /// the first one of the next repetition.
+    pub fn get_color_at(&self, offset: f64) -> Rgba {
+        interpolate_stops(&self.get_color_stops(), self.get_extend(), offset)
+    }
+
+    /// Returns a copy of the gradient, with the same geometry, extend mode, filter and matrix,
+    /// and the stops mapped by `f`.
+    pub fn map_stops<F: FnMut(f64, Rgba) -> (f64, Rgba)>(&self, mut f: F) -> Gradient {
+        let gradient = self.copy_without_stops();
+        for (offset, color) in self.get_color_stops() {
+            let (offset, color) = f(offset, color);
+            gradient.add_color_stop(offset, color);
+        }
+        gradient
+    }
+
+    /// Returns a copy of the gradient with the stops in the opposite direction.
+    pub fn reversed(&self) -> Gradient {
+        let gradient = self.copy_without_stops();
+        // Stops at the same offset keep their relative order, so they are added backwards.
+        for (offset, color) in self.get_color_stops().into_iter().rev() {
+            gradient.add_color_stop(1.0 - offset, color);
+        }
+        gradient
+    }
+
+    /// Returns a copy of the gradient with the offsets of the stops moved from 0..1 to
+    /// `start..end`.
+    pub fn remapped(&self, start: f64, end: f64) -> Gradient {
+        self.map_stops(|offset, color| (start + offset * (end - start), color))
+    }
+
+    fn copy_without_stops(&self) -> Gradient {
+        let gradient = match LinearGradient::try_from(self.clone()) {
+            Ok(linear) => {
+                let (x0, y0, x1, y1) = linear.get_linear_points();
+                LinearGradient::new(x0, y0, x1, y1).0
+            }
+            Err(gradient) => {
+                let radial = RadialGradient::try_from(gradient).expect("Unknown gradient type");
+                let (x0, y0, r0, x1, y1, r1) = radial.get_radial_circles();
+                RadialGradient::new(x0, y0, r0, x1, y1, r1).0
+            }
+        };
+        gradient.set_extend(self.get_extend());
+        gradient.set_filter(self.get_filter());
+        gradient.set_matrix(self.get_matrix());
+        gradient
+    }
+}
+
+// Evaluates sorted stops like pixman's gradient walker, which puts a virtual stop on each
+// side of the list depending on the extend mode.
+fn interpolate_stops(stops: &[(f64, Rgba)], extend: Extend, offset: f64) -> Rgba {
+    let transparent = Rgba::new(0.0, 0.0, 0.0, 0.0);
+    let (first, last) = match (stops.first(), stops.last()) {
+        (Some(&first), Some(&last)) => (first, last),
+        _ => return transparent,
+    };
+
+    let (offset, before, after) = match extend {
+        Extend::Repeat => (
+            offset - offset.floor(),
+            (last.0 - 1.0, last.1),
+            (first.0 + 1.0, first.1),
+        ),
+        Extend::Reflect => {
+            let offset = offset.rem_euclid(2.0);
+            (
+                if offset > 1.0 { 2.0 - offset } else { offset },
+                (-first.0, first.1),
+                (2.0 - last.0, last.1),
+            )
+        }
+        Extend::Pad => (offset, (0.0, first.1), (1.0, last.1)),
+        _ => (offset, (0.0, transparent), (1.0, transparent)),
+    };
+
+    let index = stops
+        .iter()
+        .position(|&(stop, _)| stop > offset)
+        .unwrap_or(stops.len());
+    match extend {
+        Extend::Repeat | Extend::Reflect => {}
+        _ if index == 0 => return before.1,
+        _ if index == stops.len() => return after.1,
+        _ => {}
+    }
+
+    let left = if index == 0 { before } else { stops[index - 1] };
+    let right = if index == stops.len() {
+        after
+    } else {
+        stops[index]
+    };
+    let t = (offset - left.0) / (right.0 - left.0);
+    if t.is_finite() {
+        left.1.mix(&right.1, t)
+    } else {
+        left.1
+    }
 }
 
 macro_rules! gradient_type {
     ($gradient_type: ident) => {
-        #[derive(Debug, Clone)]
+        #[derive(Clone)]
+        #[repr(transparent)]
         pub struct $gradient_type(Gradient);
 
         impl Deref for $gradient_type {
@@ -271,6 +487,31 @@ impl LinearGradient {
         }
     }
 
+    pub fn with_stops(x0: f64, y0: f64, x1: f64, y1: f64, stops: &[(f64, Rgba)]) -> LinearGradient {
+        let gradient = LinearGradient::new(x0, y0, x1, y1);
+        gradient.add_color_stops(stops);
+        gradient
+    }
+
+    /// Creates a gradient without stops across `rectangle`, like CSS `linear-gradient()` with
+    /// an angle.
+    ///
+    /// `angle` is in radians with the CSS conventions: 0 points up and angles grow clockwise.
+    /// The gradient line goes through the center of the rectangle, and its ends are where
+    /// lines perpendicular to it meet the corners.
+    pub fn from_css_angle(angle: f64, rectangle: &Rectangle) -> LinearGradient {
+        let (dx, dy) = (angle.sin(), -angle.cos());
+        let half_length = (rectangle.width * dx.abs() + rectangle.height * dy.abs()) / 2.0;
+        let cx = rectangle.x + rectangle.width / 2.0;
+        let cy = rectangle.y + rectangle.height / 2.0;
+        LinearGradient::new(
+            cx - dx * half_length,
+            cy - dy * half_length,
+            cx + dx * half_length,
+            cy + dy * half_length,
+        )
+    }
+
     pub fn get_linear_points(&self) -> (f64, f64, f64, f64) {
         unsafe {
             let mut x0 = 0.0;
@@ -291,6 +532,16 @@ impl LinearGradient {
     }
 }
 
+impl fmt::Debug for LinearGradient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.finish_debug(
+            f.debug_struct("LinearGradient")
+                .field("points", &self.get_linear_points())
+                .field("stops", &self.get_color_stops()),
+        )
+    }
+}
+
 gradient_type!(RadialGradient);
 
 impl RadialGradient {
@@ -302,6 +553,20 @@ impl RadialGradient {
         }
     }
 
+    pub fn with_stops(
+        x0: f64,
+        y0: f64,
+        r0: f64,
+        x1: f64,
+        y1: f64,
+        r1: f64,
+        stops: &[(f64, Rgba)],
+    ) -> RadialGradient {
+        let gradient = RadialGradient::new(x0, y0, r0, x1, y1, r1);
+        gradient.add_color_stops(stops);
+        gradient
+    }
+
     pub fn get_radial_circles(&self) -> (f64, f64, f64, f64, f64, f64) {
         unsafe {
             let mut x0 = 0.0;
@@ -326,8 +591,107 @@ impl RadialGradient {
     }
 }
 
+impl fmt::Debug for RadialGradient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.finish_debug(
+            f.debug_struct("RadialGradient")
+                .field("circles", &self.get_radial_circles())
+                .field("stops", &self.get_color_stops()),
+        )
+    }
+}
+
+// Gradients are serialized as their geometry and list of stops, with the extend mode and
+// matrix.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_crate")]
+struct LinearGradientData {
+    points: (f64, f64, f64, f64),
+    stops: Vec<(f64, Rgba)>,
+    extend: Extend,
+    matrix: Matrix,
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for LinearGradient {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        LinearGradientData {
+            points: self.get_linear_points(),
+            stops: self.get_color_stops(),
+            extend: self.get_extend(),
+            matrix: self.get_matrix(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for LinearGradient {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<LinearGradient, D::Error> {
+        let data = LinearGradientData::deserialize(deserializer)?;
+        let (x0, y0, x1, y1) = data.points;
+        let gradient = LinearGradient::with_stops(x0, y0, x1, y1, &data.stops);
+        set_deserialized_state(&gradient, data.extend, data.matrix).map_err(D::Error::custom)?;
+        Ok(gradient)
+    }
+}
+
+// Sets the extend mode and matrix of a deserialized pattern, failing instead of putting it
+// in an error state if the matrix is not invertible.
+#[cfg(feature = "serde")]
+fn set_deserialized_state(pattern: &Pattern, extend: Extend, matrix: Matrix) -> Result<(), Error> {
+    matrix.try_invert()?;
+    pattern.set_extend(extend);
+    pattern.set_matrix(matrix);
+    pattern.status()
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_crate")]
+struct RadialGradientData {
+    circles: (f64, f64, f64, f64, f64, f64),
+    stops: Vec<(f64, Rgba)>,
+    extend: Extend,
+    matrix: Matrix,
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for RadialGradient {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RadialGradientData {
+            circles: self.get_radial_circles(),
+            stops: self.get_color_stops(),
+            extend: self.get_extend(),
+            matrix: self.get_matrix(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for RadialGradient {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<RadialGradient, D::Error> {
+        let data = RadialGradientData::deserialize(deserializer)?;
+        let (x0, y0, r0, x1, y1, r1) = data.circles;
+        let gradient = RadialGradient::with_stops(x0, y0, r0, x1, y1, r1, &data.stops);
+        set_deserialized_state(&gradient, data.extend, data.matrix).map_err(D::Error::custom)?;
+        Ok(gradient)
+    }
+}
+
 pattern_type!(SurfacePattern = Surface);
 
+impl fmt::Debug for SurfacePattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.finish_debug(
+            f.debug_struct("SurfacePattern")
+                .field("surface_type", &self.get_surface().get_type()),
+        )
+    }
+}
+
 impl SurfacePattern {
     pub fn create(surface: &Surface) -> SurfacePattern {
         unsafe {
@@ -349,6 +713,15 @@ impl SurfacePattern {
 
 pattern_type!(Mesh = Mesh);
 
+impl fmt::Debug for Mesh {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.finish_debug(
+            f.debug_struct("Mesh")
+                .field("patch_count", &self.get_patch_count()),
+        )
+    }
+}
+
 impl Mesh {
     pub fn new() -> Mesh {
         unsafe { Mesh(Pattern::from_raw_full(ffi::cairo_pattern_create_mesh())) }
@@ -435,6 +808,10 @@ impl Mesh {
         self.status().expect("Failed to set corner color rgba");
     }
 
+    pub fn set_corner_color(&self, corner: MeshCorner, color: Rgba) {
+        self.set_corner_color_rgba(corner, color.red, color.green, color.blue, color.alpha)
+    }
+
     pub fn get_corner_color_rgba(
         &self,
         patch_num: usize,
@@ -500,3 +877,167 @@ fn try_from() {
     assert!(LinearGradient::try_from(gradient).is_ok());
     assert!(LinearGradient::try_from(pattern).is_ok());
 }
+
+#[test]
+fn colors() {
+    let color = Rgba::new(0.1, 0.2, 0.3, 0.4);
+    assert_eq!(SolidPattern::from_color(color).get_color(), color);
+
+    let gradient = LinearGradient::new(0., 0., 1., 1.);
+    gradient.add_color_stop(0.5, color);
+    assert_eq!(gradient.get_color_stop(0), (0.5, color));
+
+    let mesh = Mesh::new();
+    mesh.begin_patch();
+    mesh.move_to(0., 0.);
+    mesh.line_to(1., 0.);
+    mesh.line_to(1., 1.);
+    mesh.set_corner_color(MeshCorner::MeshCorner1, color);
+    mesh.end_patch();
+    assert_eq!(
+        mesh.get_corner_color_rgba(0, MeshCorner::MeshCorner1),
+        color.into()
+    );
+}
+
+#[test]
+fn color_at() {
+    let red = Rgba::rgb(1., 0., 0.);
+    let blue = Rgba::rgb(0., 0., 1.);
+    let gradient = LinearGradient::with_stops(0., 0., 1., 0., &[(0.25, red), (0.75, blue)]);
+    assert_eq!(gradient.get_color_stops(), [(0.25, red), (0.75, blue)]);
+
+    gradient.set_extend(Extend::Pad);
+    assert_eq!(gradient.get_color_at(-1.), red);
+    assert_eq!(gradient.get_color_at(0.5), red.mix(&blue, 0.5));
+    assert_eq!(gradient.get_color_at(2.), blue);
+
+    gradient.set_extend(Extend::None);
+    assert_eq!(gradient.get_color_at(0.1).alpha, 0.);
+    assert_eq!(gradient.get_color_at(0.5), red.mix(&blue, 0.5));
+    assert_eq!(gradient.get_color_at(0.9).alpha, 0.);
+
+    gradient.set_extend(Extend::Repeat);
+    assert_eq!(gradient.get_color_at(1.), blue.mix(&red, 0.5));
+    assert_eq!(gradient.get_color_at(1.25), red);
+
+    gradient.set_extend(Extend::Reflect);
+    assert_eq!(gradient.get_color_at(1.75), gradient.get_color_at(0.25));
+    assert_eq!(gradient.get_color_at(0.9), blue);
+
+    let sharp = LinearGradient::with_stops(0., 0., 1., 0., &[(0.5, red), (0.5, blue)]);
+    sharp.set_extend(Extend::Pad);
+    assert_eq!(sharp.get_color_at(0.4), red);
+    assert_eq!(sharp.get_color_at(0.5), blue);
+}
+
+#[test]
+fn reversed_and_remapped() {
+    let red = Rgba::rgb(1., 0., 0.);
+    let blue = Rgba::rgb(0., 0., 1.);
+    let radial = RadialGradient::with_stops(0., 0., 1., 0., 0., 5., &[(0., red), (0.2, blue)]);
+    radial.set_extend(Extend::Reflect);
+
+    let reversed = RadialGradient::try_from(radial.reversed()).unwrap();
+    assert_eq!(reversed.get_radial_circles(), (0., 0., 1., 0., 0., 5.));
+    assert_eq!(reversed.get_extend(), Extend::Reflect);
+    assert_eq!(reversed.get_color_stops(), [(0.8, blue), (1., red)]);
+
+    let remapped = radial.remapped(0.5, 1.);
+    assert_eq!(remapped.get_color_stops(), [(0.5, red), (0.6, blue)]);
+}
+
+#[test]
+fn css_angle() {
+    let rectangle = Rectangle {
+        x: 10.,
+        y: 0.,
+        width: 100.,
+        height: 50.,
+    };
+    let points = |degrees: f64| {
+        let (x0, y0, x1, y1) =
+            LinearGradient::from_css_angle(degrees.to_radians(), &rectangle).get_linear_points();
+        [x0, y0, x1, y1]
+            .iter()
+            .map(|v| (v * 1e6).round() / 1e6)
+            .collect::<Vec<f64>>()
+    };
+    assert_eq!(points(0.), [60., 50., 60., 0.]);
+    assert_eq!(points(90.), [10., 25., 110., 25.]);
+    assert_eq!(points(180.), [60., 0., 60., 50.]);
+    // The ends are on the lines through the corners perpendicular to the gradient line.
+    let p = points(45.);
+    let (dx, dy) = (p[2] - p[0], p[3] - p[1]);
+    assert!(((110. - p[2]) * dx + (0. - p[3]) * dy).abs() < 1e-6);
+    assert!(((10. - p[0]) * dx + (50. - p[1]) * dy).abs() < 1e-6);
+}
+
+#[test]
+fn kind() {
+    let linear = LinearGradient::new(0., 0., 1., 1.);
+    let pattern = Pattern::clone(&linear);
+    match pattern.kind() {
+        PatternKind::Linear(gradient) => assert_eq!(gradient.get_linear_points(), (0., 0., 1., 1.)),
+        kind => panic!("unexpected {:?}", kind),
+    }
+    match Pattern::clone(&Mesh::new()).kind() {
+        PatternKind::Mesh(mesh) => assert_eq!(mesh.get_patch_count(), 0),
+        kind => panic!("unexpected {:?}", kind),
+    }
+    match Pattern::clone(&SolidPattern::from_rgb(1., 0., 0.)).kind() {
+        PatternKind::Solid(solid) => assert_eq!(solid.get_rgba(), (1., 0., 0., 1.)),
+        kind => panic!("unexpected {:?}", kind),
+    }
+}
+
+#[test]
+fn debug() {
+    let radial =
+        RadialGradient::with_stops(1., 2., 3., 4., 5., 6., &[(0.5, Rgba::rgb(0., 0., 1.))]);
+    let debug = format!("{:?}", Pattern::clone(&radial));
+    assert!(debug.starts_with(
+        "RadialGradient { circles: (1.0, 2.0, 3.0, 4.0, 5.0, 6.0), stops: [(0.5, Rgba"
+    ));
+    assert!(debug.contains("extend: Pad"));
+    assert!(debug.contains("matrix: Matrix { xx: 1.0"));
+    assert_eq!(format!("{:?}", Gradient::clone(&radial)), debug);
+
+    let surface = ::ImageSurface::create(::Format::ARgb32, 1, 1).unwrap();
+    let debug = format!("{:?}", SurfacePattern::create(&surface));
+    assert!(debug.starts_with("SurfacePattern { surface_type: Image, extend: None"));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde() {
+    let linear = LinearGradient::with_stops(0., 1., 2., 3., &[(0.5, Rgba::rgb(1., 0., 0.))]);
+    linear.set_extend(Extend::Reflect);
+    let json = ::serde_json::to_string(&linear).unwrap();
+    assert_eq!(
+        json,
+        concat!(
+            r#"{"points":[0.0,1.0,2.0,3.0],"#,
+            r#""stops":[[0.5,{"red":1.0,"green":0.0,"blue":0.0,"alpha":1.0}]],"#,
+            r#""extend":"Reflect","#,
+            r#""matrix":{"xx":1.0,"yx":0.0,"xy":0.0,"yy":1.0,"x0":0.0,"y0":0.0}}"#
+        )
+    );
+    let linear: LinearGradient = ::serde_json::from_str(&json).unwrap();
+    assert_eq!(linear.get_linear_points(), (0., 1., 2., 3.));
+    assert_eq!(linear.get_color_stops(), [(0.5, Rgba::rgb(1., 0., 0.))]);
+    assert_eq!(linear.get_extend(), Extend::Reflect);
+
+    let radial = RadialGradient::with_stops(1., 2., 3., 4., 5., 6., &[(0., Rgba::rgb(0., 0., 1.))]);
+    let mut matrix = Matrix::identity();
+    matrix.scale(2., 2.);
+    radial.set_matrix(matrix);
+    let radial: RadialGradient =
+        ::serde_json::from_str(&::serde_json::to_string(&radial).unwrap()).unwrap();
+    assert_eq!(radial.get_radial_circles(), (1., 2., 3., 4., 5., 6.));
+    assert_eq!(radial.get_color_stops(), [(0., Rgba::rgb(0., 0., 1.))]);
+    assert_eq!(radial.get_matrix(), matrix);
+
+    let singular = json.replace(r#""xx":1.0"#, r#""xx":0.0"#);
+    assert!(::serde_json::from_str::<LinearGradient>(&singular).is_err());
+}
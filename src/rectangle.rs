@@ -1,12 +1,20 @@
 use ffi;
 #[cfg(feature = "use_glib")]
 use glib::translate::*;
+use rectangle_int::saturate;
 use std::fmt;
 #[cfg(feature = "use_glib")]
 use std::mem;
+use Matrix;
+use RectangleInt;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 #[repr(C)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub struct Rectangle {
     pub x: f64,
     pub y: f64,
@@ -83,6 +91,162 @@ impl Rectangle {
         let ptr = &*self as *const Rectangle as usize;
         ptr as *mut ffi::cairo_rectangle_t
     }
+
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Rectangle {
+        Rectangle {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Creates a rectangle from the `(x1, y1, x2, y2)` corners returned by
+    /// `Context::clip_extents`, `fill_extents`, `stroke_extents` and `path_extents`.
+    pub fn from_extents(extents: (f64, f64, f64, f64)) -> Rectangle {
+        let (x1, y1, x2, y2) = extents;
+        Rectangle::new(x1, y1, x2 - x1, y2 - y1)
+    }
+
+    /// Returns the `(x1, y1, x2, y2)` corners of the rectangle.
+    pub fn to_extents(&self) -> (f64, f64, f64, f64) {
+        (self.x, self.y, self.x + self.width, self.y + self.height)
+    }
+
+    /// Returns `true` if the rectangle has no area.
+    pub fn is_empty(&self) -> bool {
+        !(self.width > 0.0 && self.height > 0.0)
+    }
+
+    pub fn center(&self) -> (f64, f64) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    /// Returns the area covered by both rectangles, or `None` if they do not overlap.
+    pub fn intersection(&self, other: &Rectangle) -> Option<Rectangle> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let width = (self.x + self.width).min(other.x + other.width) - x;
+        let height = (self.y + self.height).min(other.y + other.height) - y;
+        if width > 0.0 && height > 0.0 {
+            Some(Rectangle::new(x, y, width, height))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the smallest rectangle containing both rectangles. Empty rectangles are
+    /// ignored.
+    pub fn union(&self, other: &Rectangle) -> Rectangle {
+        if other.is_empty() {
+            return *self;
+        }
+        if self.is_empty() {
+            return *other;
+        }
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Rectangle::new(
+            x,
+            y,
+            (self.x + self.width).max(other.x + other.width) - x,
+            (self.y + self.height).max(other.y + other.height) - y,
+        )
+    }
+
+    /// Returns `true` if (`x`, `y`) is in the rectangle, including its top and left sides but
+    /// not its bottom and right ones, so that adjacent rectangles never both contain a point.
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+
+    /// Returns `true` if `other` is entirely in the rectangle. Empty rectangles are not in any
+    /// rectangle.
+    pub fn contains_rectangle(&self, other: &Rectangle) -> bool {
+        !other.is_empty()
+            && other.x >= self.x
+            && other.y >= self.y
+            && other.x + other.width <= self.x + self.width
+            && other.y + other.height <= self.y + self.height
+    }
+
+    /// Returns `true` if the rectangles have an area in common.
+    pub fn overlaps(&self, other: &Rectangle) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Returns the rectangle grown by `dx` on the left and right and `dy` on the top and
+    /// bottom.
+    pub fn inflate(&self, dx: f64, dy: f64) -> Rectangle {
+        Rectangle::new(
+            self.x - dx,
+            self.y - dy,
+            self.width + 2.0 * dx,
+            self.height + 2.0 * dy,
+        )
+    }
+
+    /// Returns the rectangle shrunk by `dx` on the left and right and `dy` on the top and
+    /// bottom.
+    pub fn deflate(&self, dx: f64, dy: f64) -> Rectangle {
+        self.inflate(-dx, -dy)
+    }
+
+    /// Returns the smallest pixel aligned rectangle containing the rectangle.
+    pub fn to_int_outward(&self) -> RectangleInt {
+        let (x1, y1, x2, y2) = self.to_extents();
+        let (x, y) = (x1.floor() as i32, y1.floor() as i32);
+        RectangleInt::new(
+            x,
+            y,
+            saturate(x2.ceil() as i64 - i64::from(x)),
+            saturate(y2.ceil() as i64 - i64::from(y)),
+        )
+    }
+
+    /// Returns the largest pixel aligned rectangle in the rectangle, with a width or height
+    /// of 0 if the rectangle covers no whole pixel in that direction.
+    pub fn to_int_inward(&self) -> RectangleInt {
+        let (x1, y1, x2, y2) = self.to_extents();
+        let (x, y) = (x1.ceil() as i32, y1.ceil() as i32);
+        RectangleInt::new(
+            x,
+            y,
+            saturate((x2.floor() as i64 - i64::from(x)).max(0)),
+            saturate((y2.floor() as i64 - i64::from(y)).max(0)),
+        )
+    }
+
+    /// Returns the axis-aligned bounds of the rectangle transformed by `matrix`.
+    pub fn transform(&self, matrix: &Matrix) -> Rectangle {
+        let (x1, y1, x2, y2) = self.to_extents();
+        let corners = [
+            matrix.transform_point(x1, y1),
+            matrix.transform_point(x2, y1),
+            matrix.transform_point(x2, y2),
+            matrix.transform_point(x1, y2),
+        ];
+        let (mut min_x, mut min_y) = corners[0];
+        let (mut max_x, mut max_y) = corners[0];
+        for &(x, y) in &corners[1..] {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+        Rectangle::from_extents((min_x, min_y, max_x, max_y))
+    }
+}
+
+impl From<RectangleInt> for Rectangle {
+    fn from(rectangle: RectangleInt) -> Rectangle {
+        Rectangle::new(
+            f64::from(rectangle.x),
+            f64::from(rectangle.y),
+            f64::from(rectangle.width),
+            f64::from(rectangle.height),
+        )
+    }
 }
 
 impl fmt::Display for Rectangle {
@@ -90,3 +254,76 @@ impl fmt::Display for Rectangle {
         write!(f, "Rectangle")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+    use std::i32;
+
+    fn assert_near(actual: Rectangle, expected: Rectangle) {
+        let near = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        assert!(
+            near(actual.x, expected.x)
+                && near(actual.y, expected.y)
+                && near(actual.width, expected.width)
+                && near(actual.height, expected.height),
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn intersection_and_union() {
+        let a = Rectangle::new(0.0, 0.0, 10.0, 10.0);
+        let b = Rectangle::new(5.0, -5.0, 10.0, 10.0);
+        assert_eq!(a.intersection(&b), Some(Rectangle::new(5.0, 0.0, 5.0, 5.0)));
+        assert_eq!(a.union(&b), Rectangle::new(0.0, -5.0, 15.0, 15.0));
+        assert!(a.overlaps(&b));
+        assert!(!a.overlaps(&Rectangle::new(10.0, 0.0, 5.0, 5.0)));
+        assert_eq!(Rectangle::new(0.0, 0.0, -1.0, 0.0).union(&b), b);
+
+        assert!(a.contains(0.0, 9.5));
+        assert!(!a.contains(10.0, 0.0));
+        assert!(a.contains_rectangle(&Rectangle::new(0.5, 0.5, 9.5, 9.5)));
+        assert!(!a.contains_rectangle(&b));
+    }
+
+    #[test]
+    fn inflate_and_center() {
+        let a = Rectangle::new(0.0, 0.0, 10.0, 5.0);
+        assert_eq!(a.inflate(0.5, 1.0), Rectangle::new(-0.5, -1.0, 11.0, 7.0));
+        assert_eq!(a.deflate(0.5, 1.0), Rectangle::new(0.5, 1.0, 9.0, 3.0));
+        assert_eq!(a.center(), (5.0, 2.5));
+    }
+
+    #[test]
+    fn snapping() {
+        let a = Rectangle::new(0.5, -1.5, 2.0, 1.2);
+        assert_eq!(a.to_int_outward(), RectangleInt::new(0, -2, 3, 2));
+        assert_eq!(a.to_int_inward(), RectangleInt::new(1, -1, 1, 0));
+
+        // The sizes don't fit in i32 and saturate.
+        let wide = Rectangle::new(-1.0, 0.0, 2_147_483_649.0, 1.0);
+        assert_eq!(wide.to_int_outward(), RectangleInt::new(-1, 0, i32::MAX, 1));
+        assert_eq!(wide.to_int_inward(), RectangleInt::new(-1, 0, i32::MAX, 1));
+        assert_eq!(
+            Rectangle::from(RectangleInt::new(1, 2, 3, 4)),
+            Rectangle::new(1.0, 2.0, 3.0, 4.0)
+        );
+    }
+
+    #[test]
+    fn extents_and_transform() {
+        let a = Rectangle::from_extents((1.0, 2.0, 4.0, 6.0));
+        assert_eq!(a, Rectangle::new(1.0, 2.0, 3.0, 4.0));
+        assert_eq!(a.to_extents(), (1.0, 2.0, 4.0, 6.0));
+
+        let mut matrix = Matrix::identity();
+        matrix.translate(10.0, 0.0);
+        matrix.rotate(PI / 2.0);
+        // (x, y) becomes (10 - y, x).
+        assert_near(a.transform(&matrix), Rectangle::new(4.0, 1.0, 4.0, 3.0));
+    }
+}
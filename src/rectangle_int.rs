@@ -1,12 +1,19 @@
 use ffi;
 #[cfg(feature = "use_glib")]
 use glib::translate::*;
+use std::convert::TryFrom;
 use std::fmt;
+use std::i32;
 #[cfg(feature = "use_glib")]
 use std::mem;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[repr(C)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub struct RectangleInt {
     pub x: i32,
     pub y: i32,
@@ -83,6 +90,111 @@ impl RectangleInt {
         let ptr = &*self as *const RectangleInt as usize;
         ptr as *mut ffi::cairo_rectangle_int_t
     }
+
+    pub fn new(x: i32, y: i32, width: i32, height: i32) -> RectangleInt {
+        RectangleInt {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Returns `true` if the rectangle has no area.
+    pub fn is_empty(&self) -> bool {
+        self.width <= 0 || self.height <= 0
+    }
+
+    /// Returns the center, which is between pixels for odd sizes.
+    pub fn center(&self) -> (f64, f64) {
+        (
+            f64::from(self.x) + f64::from(self.width) / 2.0,
+            f64::from(self.y) + f64::from(self.height) / 2.0,
+        )
+    }
+
+    /// Returns the area covered by both rectangles, or `None` if they do not overlap.
+    pub fn intersection(&self, other: &RectangleInt) -> Option<RectangleInt> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let width = self.right().min(other.right()) - i64::from(x);
+        let height = self.bottom().min(other.bottom()) - i64::from(y);
+        if width > 0 && height > 0 {
+            // No larger than either rectangle, so the sizes fit.
+            Some(RectangleInt::new(x, y, width as i32, height as i32))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the smallest rectangle containing both rectangles. Empty rectangles are
+    /// ignored.
+    pub fn union(&self, other: &RectangleInt) -> RectangleInt {
+        if other.is_empty() {
+            return *self;
+        }
+        if self.is_empty() {
+            return *other;
+        }
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        RectangleInt::new(
+            x,
+            y,
+            saturate(self.right().max(other.right()) - i64::from(x)),
+            saturate(self.bottom().max(other.bottom()) - i64::from(y)),
+        )
+    }
+
+    /// Returns `true` if the pixel at (`x`, `y`) is in the rectangle.
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && i64::from(x) < self.right() && y >= self.y && i64::from(y) < self.bottom()
+    }
+
+    /// Returns `true` if `other` is entirely in the rectangle. Empty rectangles are not in any
+    /// rectangle.
+    pub fn contains_rectangle(&self, other: &RectangleInt) -> bool {
+        !other.is_empty()
+            && other.x >= self.x
+            && other.y >= self.y
+            && other.right() <= self.right()
+            && other.bottom() <= self.bottom()
+    }
+
+    /// Returns `true` if the rectangles have pixels in common.
+    pub fn overlaps(&self, other: &RectangleInt) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Returns the rectangle grown by `dx` on the left and right and `dy` on the top and
+    /// bottom. Coordinates and sizes saturate at the bounds of `i32`.
+    pub fn inflate(&self, dx: i32, dy: i32) -> RectangleInt {
+        RectangleInt::new(
+            self.x.saturating_sub(dx),
+            self.y.saturating_sub(dy),
+            self.width.saturating_add(dx.saturating_mul(2)),
+            self.height.saturating_add(dy.saturating_mul(2)),
+        )
+    }
+
+    /// Returns the rectangle shrunk by `dx` on the left and right and `dy` on the top and
+    /// bottom.
+    pub fn deflate(&self, dx: i32, dy: i32) -> RectangleInt {
+        self.inflate(0i32.saturating_sub(dx), 0i32.saturating_sub(dy))
+    }
+
+    // Edges in `i64`, which can't overflow.
+    fn right(&self) -> i64 {
+        i64::from(self.x) + i64::from(self.width)
+    }
+
+    fn bottom(&self) -> i64 {
+        i64::from(self.y) + i64::from(self.height)
+    }
+}
+
+pub(crate) fn saturate(value: i64) -> i32 {
+    i32::try_from(value).unwrap_or(if value < 0 { i32::MIN } else { i32::MAX })
 }
 
 impl fmt::Display for RectangleInt {
@@ -90,3 +202,64 @@ impl fmt::Display for RectangleInt {
         write!(f, "RectangleInt")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intersection_and_union() {
+        let a = RectangleInt::new(0, 0, 10, 10);
+        let b = RectangleInt::new(5, -5, 10, 10);
+        assert_eq!(a.intersection(&b), Some(RectangleInt::new(5, 0, 5, 5)));
+        assert_eq!(a.union(&b), RectangleInt::new(0, -5, 15, 15));
+        assert!(a.overlaps(&b));
+
+        // Touching rectangles do not overlap.
+        let c = RectangleInt::new(10, 0, 5, 5);
+        assert_eq!(a.intersection(&c), None);
+        assert!(!a.overlaps(&c));
+        assert_eq!(RectangleInt::new(0, 0, 0, 0).union(&c), c);
+    }
+
+    #[test]
+    fn containment() {
+        let a = RectangleInt::new(0, 0, 10, 10);
+        assert!(a.contains(0, 9));
+        assert!(!a.contains(10, 0));
+        assert!(a.contains_rectangle(&RectangleInt::new(2, 2, 8, 8)));
+        assert!(!a.contains_rectangle(&RectangleInt::new(2, 2, 9, 8)));
+        assert!(!a.contains_rectangle(&RectangleInt::new(2, 2, 0, 8)));
+    }
+
+    #[test]
+    fn inflate_and_center() {
+        let a = RectangleInt::new(0, 0, 10, 5);
+        assert_eq!(a.inflate(1, 2), RectangleInt::new(-1, -2, 12, 9));
+        assert_eq!(a.deflate(5, 1), RectangleInt::new(5, 1, 0, 3));
+        assert!(a.deflate(5, 1).is_empty());
+        assert_eq!(a.center(), (5.0, 2.5));
+    }
+
+    #[test]
+    fn extreme_coordinates() {
+        let max = i32::MAX;
+        let a = RectangleInt::new(max - 10, max - 10, 100, 100);
+        let b = RectangleInt::new(max - 5, 0, 100, max);
+        assert_eq!(
+            a.intersection(&b),
+            Some(RectangleInt::new(max - 5, max - 10, 95, 10))
+        );
+        assert!(a.contains(max, max));
+        assert!(a.contains_rectangle(&RectangleInt::new(max, max, 50, 50)));
+        assert_eq!(
+            RectangleInt::new(i32::MIN, 0, 1, 1).union(&a),
+            RectangleInt::new(i32::MIN, 0, max, max)
+        );
+        assert_eq!(a.inflate(max, max), RectangleInt::new(-10, -10, max, max));
+        assert_eq!(
+            RectangleInt::new(0, 0, 1, 1).deflate(i32::MIN, 0),
+            RectangleInt::new(-max, 0, max, 1)
+        );
+    }
+}
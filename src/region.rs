@@ -2,15 +2,19 @@
 // See the COPYRIGHT file at the top-level directory of this distribution.
 // Licensed under the MIT license, see the LICENSE file or <https://opensource.org/licenses/MIT>
 
-use enums::RegionOverlap;
+use context::Context;
+use enums::{Antialias, FillRule, Format, RegionOverlap};
 use error::Error;
 use ffi;
 #[cfg(feature = "use_glib")]
 use glib::translate::*;
 use std::fmt;
+use std::i32;
+use std::iter::FromIterator;
+use std::ops::{BitAnd, BitOr, BitXor, Sub};
 use std::ptr;
 use utils::status_to_result;
-use RectangleInt;
+use {BorrowError, ImageSurface, Path, PathSegment, RectangleInt};
 
 use ffi::cairo_region_t;
 
@@ -143,12 +147,134 @@ impl Region {
         }
     }
 
+    /// Creates a region of the pixels of `mask` whose alpha is at least `threshold`.
+    ///
+    /// Only `A1`, `A8` and `ARgb32` surfaces have alpha data, other formats return
+    /// `Error::InvalidFormat`.
+    pub fn from_mask(mask: &ImageSurface, threshold: u8) -> Result<Region, BorrowError> {
+        let format = mask.get_format();
+        let alpha: fn(&[u8], usize) -> u8 = match format {
+            Format::A1 => a1_alpha,
+            Format::A8 => |row, x| row[x],
+            Format::ARgb32 => |row, x| {
+                let i = x * 4;
+                (u32::from_ne_bytes([row[i], row[i + 1], row[i + 2], row[i + 3]]) >> 24) as u8
+            },
+            _ => return Err(BorrowError::from(Error::InvalidFormat)),
+        };
+        let width = mask.get_width() as usize;
+        let stride = mask.get_stride() as usize;
+
+        let mut rectangles = Vec::new();
+        mask.with_data(|data| {
+            // Rows with the same runs as the previous one extend its rectangles, which are
+            // only added once a row differs.
+            let mut band: Vec<RectangleInt> = Vec::new();
+            for (y, row) in data.chunks(stride).enumerate() {
+                let mut runs = Vec::new();
+                let mut x = 0;
+                while x < width {
+                    if alpha(row, x) < threshold {
+                        x += 1;
+                        continue;
+                    }
+                    let start = x;
+                    while x < width && alpha(row, x) >= threshold {
+                        x += 1;
+                    }
+                    runs.push((start as i32, (x - start) as i32));
+                }
+
+                let same = runs.len() == band.len()
+                    && runs
+                        .iter()
+                        .zip(&band)
+                        .all(|(&(x, width), r)| r.x == x && r.width == width);
+                if same {
+                    for rectangle in &mut band {
+                        rectangle.height += 1;
+                    }
+                } else {
+                    rectangles.append(&mut band);
+                    band = runs
+                        .into_iter()
+                        .map(|(x, width)| RectangleInt {
+                            x,
+                            y: y as i32,
+                            width,
+                            height: 1,
+                        })
+                        .collect();
+                }
+            }
+            rectangles.append(&mut band);
+        })?;
+        Ok(Region::create_rectangles(&rectangles))
+    }
+
+    /// Creates a region of the pixels whose center is inside `path` filled with `fill_rule`,
+    /// with curves flattened to within `tolerance` pixels.
+    pub fn from_path(path: &Path, fill_rule: FillRule, tolerance: f64) -> Result<Region, Error> {
+        let cr = Context::scratch();
+        cr.set_fill_rule(fill_rule);
+        cr.set_tolerance(tolerance);
+        cr.append_path(path);
+        let (x1, y1, x2, y2) = cr.fill_extents();
+        let (x, y) = (x1.floor(), y1.floor());
+        let (width, height) = (x2.ceil() - x, y2.ceil() - y);
+        if !(width > 0.0 && height > 0.0) {
+            return Ok(Region::create());
+        }
+        let fits = |value: f64| value >= f64::from(i32::MIN) && value <= f64::from(i32::MAX);
+        if !(fits(x) && fits(y) && fits(width) && fits(height)) {
+            return Err(Error::InvalidSize);
+        }
+        let (x, y) = (x as i32, y as i32);
+
+        let mask = ImageSurface::create(Format::A8, width as i32, height as i32)?;
+        {
+            let cr = Context::new(&mask);
+            cr.translate(-f64::from(x), -f64::from(y));
+            cr.set_antialias(Antialias::None);
+            cr.set_fill_rule(fill_rule);
+            cr.set_tolerance(tolerance);
+            cr.append_path(path);
+            cr.fill();
+            cr.status()?;
+        }
+        let region = Region::from_mask(&mask, 1).map_err(|error| match error {
+            BorrowError::Cairo(error) => error,
+            BorrowError::NonExclusive => unreachable!(),
+        })?;
+        region.translate(x, y);
+        Ok(region)
+    }
+
+    /// Returns a path of the rectangles of the region, to fill with `Context::append_path`.
+    pub fn to_path(&self) -> Path {
+        Path::from_segments(self.iter().flat_map(|r| {
+            let (x1, y1) = (f64::from(r.x), f64::from(r.y));
+            let (x2, y2) = (f64::from(r.x + r.width), f64::from(r.y + r.height));
+            vec![
+                PathSegment::MoveTo((x1, y1)),
+                PathSegment::LineTo((x2, y1)),
+                PathSegment::LineTo((x2, y2)),
+                PathSegment::LineTo((x1, y2)),
+                PathSegment::ClosePath,
+            ]
+        }))
+    }
+
     pub fn copy(&self) -> Region {
         unsafe { Self::from_raw_full(ffi::cairo_region_copy(self.0.as_ptr())) }
     }
 
-    pub fn get_extents(&self, rectangle: &mut RectangleInt) {
-        unsafe { ffi::cairo_region_get_extents(self.0.as_ptr(), rectangle.to_raw_none()) }
+    pub fn get_extents(&self) -> RectangleInt {
+        unsafe {
+            let rectangle: RectangleInt = ::std::mem::zeroed();
+            ffi::cairo_region_get_extents(self.0.as_ptr(), rectangle.to_raw_none());
+            rectangle
+        }
     }
 
     pub fn num_rectangles(&self) -> i32 {
@@ -163,6 +289,15 @@ impl Region {
         }
     }
 
+    /// Returns an iterator over the rectangles of the region, which do not overlap.
+    pub fn iter(&self) -> RegionRectangles {
+        RegionRectangles {
+            region: self,
+            start: 0,
+            end: self.num_rectangles(),
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         unsafe { ffi::cairo_region_is_empty(self.0.as_ptr()).as_bool() }
     }
@@ -244,8 +379,301 @@ impl Region {
     }
 }
 
+impl<'a> IntoIterator for &'a Region {
+    type Item = RectangleInt;
+    type IntoIter = RegionRectangles<'a>;
+
+    fn into_iter(self) -> RegionRectangles<'a> {
+        self.iter()
+    }
+}
+
+impl FromIterator<RectangleInt> for Region {
+    fn from_iter<I: IntoIterator<Item = RectangleInt>>(iter: I) -> Region {
+        let rectangles: Vec<RectangleInt> = iter.into_iter().collect();
+        Region::create_rectangles(&rectangles)
+    }
+}
+
+impl Extend<RectangleInt> for Region {
+    fn extend<I: IntoIterator<Item = RectangleInt>>(&mut self, iter: I) {
+        for rectangle in iter {
+            self.union_rectangle(&rectangle)
+                .expect("Failed to add a rectangle to a region");
+        }
+    }
+}
+
+// Regions are reference counted, so operators work on a copy of the left operand instead of
+// modifying it, even when it is taken by value.
+macro_rules! region_operator {
+    ($trait:ident, $method:ident, $operation:ident) => {
+        impl<'a, 'b> $trait<&'b Region> for &'a Region {
+            type Output = Region;
+
+            fn $method(self, other: &'b Region) -> Region {
+                let region = self.copy();
+                region.$operation(other).expect(concat!(
+                    "Failed to compute a region ",
+                    stringify!($operation)
+                ));
+                region
+            }
+        }
+
+        impl<'a> $trait<&'a Region> for Region {
+            type Output = Region;
+
+            fn $method(self, other: &'a Region) -> Region {
+                $trait::$method(&self, other)
+            }
+        }
+
+        impl $trait<Region> for Region {
+            type Output = Region;
+
+            fn $method(self, other: Region) -> Region {
+                $trait::$method(&self, &other)
+            }
+        }
+    };
+}
+
+region_operator!(BitOr, bitor, union);
+region_operator!(BitAnd, bitand, intersect);
+region_operator!(Sub, sub, subtract);
+region_operator!(BitXor, bitxor, xor);
+
+/// Iterator over the rectangles of a `Region`, returned by `Region::iter`.
+#[derive(Debug, Clone)]
+pub struct RegionRectangles<'a> {
+    region: &'a Region,
+    start: i32,
+    end: i32,
+}
+
+impl<'a> RegionRectangles<'a> {
+    // Region methods take `&self`, so the region can lose rectangles while it is iterated,
+    // and cairo does not check the index of `cairo_region_get_rectangle`.
+    fn clamp_end(&mut self) {
+        self.end = self.end.min(self.region.num_rectangles());
+    }
+}
+
+impl<'a> Iterator for RegionRectangles<'a> {
+    type Item = RectangleInt;
+
+    fn next(&mut self) -> Option<RectangleInt> {
+        self.clamp_end();
+        if self.start >= self.end {
+            return None;
+        }
+        self.start += 1;
+        Some(self.region.get_rectangle(self.start - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let end = self.end.min(self.region.num_rectangles());
+        let len = (end - self.start).max(0) as usize;
+        (len, Some(len))
+    }
+}
+
+impl<'a> DoubleEndedIterator for RegionRectangles<'a> {
+    fn next_back(&mut self) -> Option<RectangleInt> {
+        self.clamp_end();
+        if self.start >= self.end {
+            return None;
+        }
+        self.end -= 1;
+        Some(self.region.get_rectangle(self.end))
+    }
+}
+
+impl<'a> ExactSizeIterator for RegionRectangles<'a> {}
+
 impl fmt::Display for Region {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Region")
     }
 }
+
+// Returns the alpha of pixel `x` of a row of `A1` data, where pixels are bits of native
+// endian 32 bit words, starting from the least significant bit on little endian machines.
+fn a1_alpha(row: &[u8], x: usize) -> u8 {
+    let i = x / 32 * 4;
+    let word = u32::from_ne_bytes([row[i], row[i + 1], row[i + 2], row[i + 3]]);
+    let bit = if cfg!(target_endian = "little") {
+        x % 32
+    } else {
+        31 - x % 32
+    };
+    if word & (1 << bit) != 0 {
+        255
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rectangle(x: i32, y: i32, width: i32, height: i32) -> RectangleInt {
+        RectangleInt {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn operators() {
+        let a = Region::create_rectangle(&rectangle(0, 0, 10, 10));
+        let b = Region::create_rectangle(&rectangle(5, 0, 10, 10));
+
+        assert_eq!((&a | &b).get_extents(), rectangle(0, 0, 15, 10));
+        assert_eq!(
+            (&a & &b).iter().collect::<Vec<_>>(),
+            [rectangle(5, 0, 5, 10)]
+        );
+        assert_eq!(
+            (&a - &b).iter().collect::<Vec<_>>(),
+            [rectangle(0, 0, 5, 10)]
+        );
+        assert_eq!(
+            (a.clone() ^ b).iter().collect::<Vec<_>>(),
+            [rectangle(0, 0, 5, 10), rectangle(10, 0, 5, 10)]
+        );
+        // The operands are left unchanged.
+        assert_eq!(a.iter().collect::<Vec<_>>(), [rectangle(0, 0, 10, 10)]);
+    }
+
+    #[test]
+    fn iteration() {
+        let mut region: Region = vec![rectangle(0, 0, 2, 2), rectangle(0, 4, 2, 2)]
+            .into_iter()
+            .collect();
+        region.extend(vec![rectangle(1, 0, 2, 2)]);
+
+        let rectangles = region.iter();
+        assert_eq!(rectangles.len(), 2);
+        assert_eq!(
+            rectangles.rev().collect::<Vec<_>>(),
+            [rectangle(0, 4, 2, 2), rectangle(0, 0, 3, 2)]
+        );
+        assert_eq!(region.get_extents(), rectangle(0, 0, 3, 6));
+        assert!(Region::create().iter().next().is_none());
+    }
+
+    #[test]
+    fn iteration_while_shrinking() {
+        let region = Region::create_rectangles(&[
+            rectangle(0, 0, 1, 1),
+            rectangle(0, 2, 1, 1),
+            rectangle(0, 4, 1, 1),
+        ]);
+        let mut rectangles = region.iter();
+        assert_eq!(rectangles.next(), Some(rectangle(0, 0, 1, 1)));
+        region.subtract_rectangle(&rectangle(0, 0, 1, 6)).unwrap();
+        assert_eq!(rectangles.len(), 0);
+        assert_eq!(rectangles.next(), None);
+        assert_eq!(rectangles.next_back(), None);
+    }
+
+    // Returns a surface of `format` with the rectangles filled with an alpha of 0.5 on the left
+    // half, or 1 for `A1`, and 1 on the right one.
+    fn mask(format: Format, rectangles: &[RectangleInt]) -> ImageSurface {
+        let surface = ImageSurface::create(format, 64, 8).unwrap();
+        {
+            let cr = Context::new(&surface);
+            for r in rectangles {
+                cr.rectangle(
+                    f64::from(r.x),
+                    f64::from(r.y),
+                    f64::from(r.width),
+                    f64::from(r.height),
+                );
+            }
+            cr.clip();
+            let alpha = if format == Format::A1 { 1.0 } else { 0.5 };
+            cr.set_source_rgba(0.0, 0.0, 0.0, alpha);
+            cr.paint();
+            cr.rectangle(32.0, 0.0, 32.0, 8.0);
+            cr.set_source_rgba(0.0, 0.0, 0.0, 1.0);
+            cr.fill();
+        }
+        surface
+    }
+
+    #[test]
+    fn from_mask() {
+        let rectangles = [rectangle(1, 1, 40, 3), rectangle(33, 5, 2, 1)];
+        let expected: Region = rectangles.iter().cloned().collect();
+        for &format in &[Format::A8, Format::ARgb32, Format::A1] {
+            let surface = mask(format, &rectangles);
+            assert_eq!(
+                Region::from_mask(&surface, 1).unwrap(),
+                expected,
+                "{:?}",
+                format
+            );
+        }
+
+        let right = Region::create_rectangle(&rectangle(32, 1, 9, 3))
+            | Region::create_rectangle(&rectangle(33, 5, 2, 1));
+        let surface = mask(Format::A8, &rectangles);
+        assert_eq!(Region::from_mask(&surface, 192).unwrap(), right);
+        assert_eq!(
+            Region::from_mask(&surface, 192).unwrap().num_rectangles(),
+            2
+        );
+
+        let surface = ImageSurface::create(Format::Rgb24, 1, 1).unwrap();
+        match Region::from_mask(&surface, 1) {
+            Err(BorrowError::Cairo(Error::InvalidFormat)) => {}
+            result => panic!("unexpected {:?}", result),
+        }
+    }
+
+    #[test]
+    fn paths() {
+        let region = Region::create_rectangles(&[rectangle(-3, 2, 4, 2), rectangle(5, 2, 1, 6)]);
+        let path = region.to_path();
+        assert_eq!(
+            Region::from_path(&path, FillRule::Winding, 0.1).unwrap(),
+            region
+        );
+
+        // A triangle covers the pixels whose center is inside it.
+        let triangle = Path::from_segments(vec![
+            PathSegment::MoveTo((0.0, 0.0)),
+            PathSegment::LineTo((4.5, 0.0)),
+            PathSegment::LineTo((0.0, 4.5)),
+            PathSegment::ClosePath,
+        ]);
+        let region = Region::from_path(&triangle, FillRule::EvenOdd, 0.1).unwrap();
+        assert_eq!(
+            region.iter().collect::<Vec<_>>(),
+            [
+                rectangle(0, 0, 4, 1),
+                rectangle(0, 1, 3, 1),
+                rectangle(0, 2, 2, 1),
+                rectangle(0, 3, 1, 1)
+            ]
+        );
+
+        // Extents beyond the size of image surfaces are rejected.
+        let huge = Region::create_rectangle(&rectangle(0, 0, 1 << 20, 1 << 20)).to_path();
+        assert_eq!(
+            Region::from_path(&huge, FillRule::Winding, 0.1).unwrap_err(),
+            Error::InvalidSize
+        );
+
+        let empty = Path::from_segments(vec![]);
+        assert!(Region::from_path(&empty, FillRule::Winding, 0.1)
+            .unwrap()
+            .is_empty());
+    }
+}
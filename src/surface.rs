@@ -18,8 +18,21 @@ use utils::status_to_result;
 
 use device::Device;
 use image_surface::ImageSurface;
+#[cfg(any(feature = "pdf", feature = "dox"))]
+use pdf::PdfSurface;
+#[cfg(any(feature = "ps", feature = "dox"))]
+use ps::PsSurface;
+#[cfg(any(target_os = "macos", target_os = "ios", feature = "dox"))]
+use quartz_surface::QuartzSurface;
+use recording_surface::RecordingSurface;
 use rectangle::Rectangle;
 use rectangle_int::RectangleInt;
+#[cfg(any(feature = "svg", feature = "dox"))]
+use svg::SvgSurface;
+#[cfg(any(all(windows, feature = "win32-surface"), feature = "dox"))]
+use win32_surface::Win32Surface;
+#[cfg(any(feature = "xcb", feature = "dox"))]
+use xcb::XCBSurface;
 
 #[derive(Debug)]
 pub struct Surface(ptr::NonNull<ffi::cairo_surface_t>);
@@ -340,6 +353,78 @@ impl Surface {
     pub fn get_type(&self) -> SurfaceType {
         unsafe { SurfaceType::from(ffi::cairo_surface_get_type(self.0.as_ptr())) }
     }
+
+    /// Returns whether `self` is a `T`, e.g. `surface.is::<PdfSurface>()`.
+    pub fn is<T: SurfaceDowncast>(&self) -> bool {
+        self.get_type() == T::SURFACE_TYPE
+    }
+
+    /// Borrows `self` as a `T` if it has the right type.
+    ///
+    /// Unlike the `TryFrom<Surface>` implementations, this does not consume `self`.
+    pub fn downcast_ref<T: SurfaceDowncast>(&self) -> Option<&T> {
+        if self.is::<T>() {
+            // Surface types are `#[repr(transparent)]` wrappers of `Surface`.
+            Some(unsafe { &*(self as *const Surface as *const T) })
+        } else {
+            None
+        }
+    }
+
+    /// Returns `self` borrowed as its specific type, to `match` on.
+    pub fn kind(&self) -> SurfaceKind {
+        match self.get_type() {
+            SurfaceType::Image => SurfaceKind::Image(self.downcast_ref().unwrap()),
+            #[cfg(any(feature = "pdf", feature = "dox"))]
+            SurfaceType::Pdf => SurfaceKind::Pdf(self.downcast_ref().unwrap()),
+            #[cfg(any(feature = "ps", feature = "dox"))]
+            SurfaceType::Ps => SurfaceKind::Ps(self.downcast_ref().unwrap()),
+            #[cfg(any(feature = "svg", feature = "dox"))]
+            SurfaceType::Svg => SurfaceKind::Svg(self.downcast_ref().unwrap()),
+            SurfaceType::Recording => SurfaceKind::Recording(self.downcast_ref().unwrap()),
+            #[cfg(any(feature = "xcb", feature = "dox"))]
+            SurfaceType::Xcb => SurfaceKind::Xcb(self.downcast_ref().unwrap()),
+            #[cfg(any(all(windows, feature = "win32-surface"), feature = "dox"))]
+            SurfaceType::Win32 => SurfaceKind::Win32(self.downcast_ref().unwrap()),
+            #[cfg(any(target_os = "macos", target_os = "ios", feature = "dox"))]
+            SurfaceType::Quartz => SurfaceKind::Quartz(self.downcast_ref().unwrap()),
+            _ => SurfaceKind::Other(self),
+        }
+    }
+}
+
+/// Surface types `Surface::downcast_ref` can return.
+///
+/// # Safety
+///
+/// Implementors must be `#[repr(transparent)]` wrappers of `Surface` whose values all have
+/// the `SURFACE_TYPE` type.
+pub unsafe trait SurfaceDowncast {
+    const SURFACE_TYPE: SurfaceType;
+}
+
+/// A `Surface` borrowed as its specific type, returned by `Surface::kind`.
+///
+/// `Other` holds surfaces of types this crate has no wrapper for, or whose feature is
+/// disabled.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum SurfaceKind<'a> {
+    Image(&'a ImageSurface),
+    #[cfg(any(feature = "pdf", feature = "dox"))]
+    Pdf(&'a PdfSurface),
+    #[cfg(any(feature = "ps", feature = "dox"))]
+    Ps(&'a PsSurface),
+    #[cfg(any(feature = "svg", feature = "dox"))]
+    Svg(&'a SvgSurface),
+    Recording(&'a RecordingSurface),
+    #[cfg(any(feature = "xcb", feature = "dox"))]
+    Xcb(&'a XCBSurface),
+    #[cfg(any(all(windows, feature = "win32-surface"), feature = "dox"))]
+    Win32(&'a Win32Surface),
+    #[cfg(any(target_os = "macos", target_os = "ios", feature = "dox"))]
+    Quartz(&'a QuartzSurface),
+    Other(&'a Surface),
 }
 
 #[derive(Debug)]
@@ -376,7 +461,9 @@ impl fmt::Display for MappedImageSurface {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use constants::MIME_TYPE_PNG;
+    use context::Context;
     use Format;
     use ImageSurface;
 
@@ -391,4 +478,31 @@ mod tests {
         let data = surface.get_mime_data(MIME_TYPE_PNG).unwrap();
         assert_eq!(data, &[1u8, 10u8]);
     }
+
+    #[test]
+    fn downcast_borrowed_target() {
+        let surface = ImageSurface::create(Format::ARgb32, 20, 10).unwrap();
+        let cr = Context::new(&surface);
+        let target = cr.get_target();
+
+        assert!(target.is::<ImageSurface>());
+        assert_eq!(
+            target.downcast_ref::<ImageSurface>().unwrap().get_width(),
+            20
+        );
+        assert!(target.downcast_ref::<RecordingSurface>().is_none());
+        match target.kind() {
+            SurfaceKind::Image(image) => assert_eq!(image.get_height(), 10),
+            kind => panic!("unexpected {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn kind_of_recording_surface() {
+        let surface = RecordingSurface::create(::Content::ColorAlpha, None).unwrap();
+        match surface.kind() {
+            SurfaceKind::Recording(_) => {}
+            kind => panic!("unexpected {:?}", kind),
+        }
+    }
 }
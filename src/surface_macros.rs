@@ -2,8 +2,13 @@
 macro_rules! declare_surface {
     ($surf_name:ident, $surf_type:expr) => {
         #[derive(Debug)]
+        #[repr(transparent)]
         pub struct $surf_name(Surface);
 
+        unsafe impl ::surface::SurfaceDowncast for $surf_name {
+            const SURFACE_TYPE: SurfaceType = $surf_type;
+        }
+
         impl TryFrom<Surface> for $surf_name {
             type Error = Surface;
 